@@ -0,0 +1,96 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use super::text;
+
+pub struct Import {
+    pub start: usize,
+    pub end: usize,
+    pub path: String
+}
+
+/// Finds the `@import` rules at the top level of a stylesheet. Only plain
+/// imports are supported, either as a string or as a `url()`.
+pub fn find(css: &str) -> Result<Vec<Import>, (usize, String)> {
+    let mut imports = vec![];
+    let mut depth = 0usize;
+    let mut index = 0;
+
+    while index < css.len() {
+        if let Some(end) = text::skip_opaque(css, index) {
+            index = end;
+            continue;
+        }
+        match css.as_bytes()[index] {
+            b'{' => depth += 1,
+            b'}' => depth = depth.saturating_sub(1),
+            b'@' if depth == 0 && css[index..].starts_with("@import") => {
+                let end = statement_end(css, index);
+                let prelude = css[index + "@import".len()..end].trim_right_matches(';').trim();
+                let path = parse_path(prelude).ok_or_else(|| {
+                    (index, format!("Unsupported @import {}, expected a single path", prelude))
+                })?;
+                imports.push(Import {
+                    start: index,
+                    end,
+                    path
+                });
+                index = end;
+                continue;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    Ok(imports)
+}
+
+fn statement_end(css: &str, start: usize) -> usize {
+    let mut index = start;
+    while index < css.len() {
+        if let Some(end) = text::skip_opaque(css, index) {
+            index = end;
+            continue;
+        }
+        if css.as_bytes()[index] == b';' {
+            return index + 1;
+        }
+        index += 1;
+    }
+    css.len()
+}
+
+fn parse_path(prelude: &str) -> Option<String> {
+    let inner = if prelude.starts_with("url(") && prelude.ends_with(')') {
+        prelude[4..prelude.len() - 1].trim()
+    } else {
+        prelude
+    };
+
+    let unquoted = if inner.len() >= 2 && (inner.starts_with('"') || inner.starts_with('\'')) {
+        let quote = &inner[..1];
+        if !inner.ends_with(quote) {
+            return None;
+        }
+        &inner[1..inner.len() - 1]
+    } else if inner.len() != prelude.len() {
+        inner
+    } else {
+        return None;
+    };
+
+    if unquoted.is_empty() || unquoted.contains(|c: char| c == '"' || c == '\'') {
+        return None;
+    }
+
+    Some(unquoted.to_string())
+}
@@ -0,0 +1,224 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+mod imports;
+mod options;
+mod reporter;
+mod resolver;
+mod scoped;
+mod sheet;
+mod text;
+mod themes;
+mod typed;
+mod vars;
+
+use diagnostics::{self, Error};
+use files;
+use proc_macro::{Span, TokenStream};
+use rsx_primitives::rsx_stylesheet::servo_css_parser::parse_with_reporter as parse_css;
+use rsx_primitives::rsx_stylesheet::servo_css_parser::types::*;
+use rsx_primitives::rsx_stylesheet::types::Stylesheet;
+use self::options::Options;
+use self::reporter::CollectingReporter;
+use self::sheet::{Sheet, SheetSource};
+use self::vars::Undefined;
+use source::SourceMap;
+use std::path::{Path, PathBuf};
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let mut options = Options::default();
+    let expanded = options::parse(input, &mut options).and_then(|input| expand_sheets(&options, input));
+
+    match expanded {
+        Ok(expanded) => expanded,
+        Err(err) => {
+            err.emit();
+            // A named struct is expanded where items are expected, and
+            // `unreachable!()` would be a second error there.
+            if options.name.is_some() {
+                TokenStream::empty()
+            } else {
+                diagnostics::placeholder()
+            }
+        }
+    }
+}
+
+fn expand_sheets(options: &Options, input: TokenStream) -> Result<TokenStream, Error> {
+    let mut sheets = load(input)?;
+
+    let variables = vars::extract(&mut sheets);
+    let themed = if options.themed {
+        Some(themes::expand(&sheets, &variables)?)
+    } else {
+        None
+    };
+
+    // Themed sheets are still parsed with their default values, so that
+    // invalid declarations are reported at compile time.
+    let undefined = if options.themed {
+        Undefined::Initial
+    } else {
+        Undefined::Error
+    };
+    for sheet in &mut sheets {
+        vars::substitute(sheet, &variables, undefined)?;
+    }
+
+    let mut rules = vec![];
+    let mut errors = vec![];
+
+    for sheet in &sheets {
+        let url = Url::parse("about::inline").unwrap();
+        let reporter = CollectingReporter::default();
+        let parsed = parse_css(
+            &sheet.css,
+            url,
+            Origin::UserAgent,
+            QuirksMode::NoQuirks,
+            MediaList::empty(),
+            &reporter
+        );
+
+        for error in reporter.into_errors() {
+            let offset = text::offset_of(&sheet.css, error.line, error.column);
+            let err = sheet.error_at(offset, error.message);
+            if options.lenient {
+                diagnostics::warning(err.span, err.message);
+            } else {
+                errors.push(err);
+            }
+        }
+
+        let stylesheet: Stylesheet = parsed.into();
+        rules.extend(stylesheet.0);
+    }
+
+    // Report every invalid declaration, returning the last one.
+    if let Some(last) = errors.pop() {
+        for err in errors {
+            err.emit();
+        }
+        return Err(last);
+    }
+
+    let paths: Vec<&PathBuf> = sheets
+        .iter()
+        .filter_map(|sheet| match sheet.source {
+            SheetSource::File { ref path, .. } => Some(path),
+            SheetSource::Inline { .. } => None
+        })
+        .collect();
+    let dependencies = files::track(&paths);
+
+    let mut stylesheet = Stylesheet(rules);
+    let expanded = if let Some(themed) = themed {
+        quote! {
+            {
+                #dependencies
+                #themed
+            }
+        }
+    } else if options.typed {
+        let mut fields = typed::fields(&stylesheet, &sheets)?;
+        if options.scoped {
+            let scope = scoped::scope(sheets.last().unwrap());
+            scoped::rewrite(&mut stylesheet, &mut fields, &scope);
+        }
+        let name = options.name.as_ref().map(String::as_str);
+        typed::expand(&mut stylesheet, &fields, name, &dependencies)
+    } else {
+        quote! {
+            {
+                #dependencies
+                #stylesheet
+            }
+        }
+    };
+
+    Ok(expanded.parse().unwrap())
+}
+
+/// Loads the stylesheet given to `css!` and everything it imports, in
+/// cascade order: imported sheets come before the sheet importing them.
+fn load(input: TokenStream) -> Result<Vec<Sheet>, Error> {
+    let mut sheets = vec![];
+
+    if let Some((path, span)) = files::literal_path(&input) {
+        let file_path = files::resolve(&path, span)?;
+        load_file(file_path, span, &mut vec![], &mut sheets)?;
+        return Ok(sheets);
+    }
+
+    // Token streams don't carry whitespace, but it's significant in CSS:
+    // ".foo .bar" and ".foo.bar" are different selectors. Rebuild the text
+    // from token positions instead of stringifying the stream.
+    let map = SourceMap::from_tokens(input);
+    let sheet = Sheet::new(map.source.clone(), SheetSource::Inline { map });
+
+    load_imports(sheet, None, &mut vec![], &mut sheets)?;
+    Ok(sheets)
+}
+
+fn load_file(path: PathBuf, span: Span, stack: &mut Vec<PathBuf>, sheets: &mut Vec<Sheet>) -> Result<(), Error> {
+    let css = files::read(&path, span)?;
+    let sheet = Sheet::new(
+        css,
+        SheetSource::File {
+            path: path.clone(),
+            span
+        }
+    );
+
+    stack.push(path.canonicalize().unwrap_or(path.clone()));
+    let base_dir = path.parent().map(Path::to_path_buf);
+    load_imports(sheet, base_dir, stack, sheets)?;
+    stack.pop();
+    Ok(())
+}
+
+fn load_imports(
+    mut sheet: Sheet,
+    base_dir: Option<PathBuf>,
+    stack: &mut Vec<PathBuf>,
+    sheets: &mut Vec<Sheet>
+) -> Result<(), Error> {
+    let imports = imports::find(&sheet.css).map_err(|(offset, message)| sheet.error_at(offset, message))?;
+
+    for import in &imports {
+        let span = sheet.span_at(import.start);
+        let path = match base_dir {
+            Some(ref base_dir) => base_dir.join(&import.path),
+            None => files::resolve(&import.path, span)?
+        };
+        if !path.is_file() {
+            let message = format!("Couldn't find imported file {}", path.to_string_lossy());
+            return Err(sheet.error_at(import.start, message));
+        }
+
+        let canonical = path.canonicalize().unwrap_or(path.clone());
+        if stack.contains(&canonical) {
+            let message = format!("Circular @import of {}", path.to_string_lossy());
+            return Err(sheet.error_at(import.start, message));
+        }
+
+        load_file(path, span, stack, sheets)?;
+    }
+
+    // The imported rules are inlined before this sheet's own rules, so the
+    // import statements themselves are no longer needed.
+    for import in &imports {
+        sheet.css = text::blank_out(&sheet.css, import.start, import.end);
+    }
+
+    sheets.push(sheet);
+    Ok(())
+}
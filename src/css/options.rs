@@ -0,0 +1,112 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use diagnostics::Error;
+use proc_macro::{Delimiter, Span, TokenNode, TokenStream, TokenTree};
+
+/// Flags given to `css!` as attributes preceding the stylesheet, such as
+/// `css!(#[lenient] "style.css")`. A `#` followed by brackets can't start
+/// valid CSS, so these never clash with id selectors.
+#[derive(Default)]
+pub struct Options {
+    /// Report invalid declarations and selectors as warnings instead of errors.
+    pub lenient: bool,
+    /// Keep `var()` references so that they can be substituted at runtime.
+    pub themed: bool,
+    /// Generate a struct with a field per class selector instead of a `Stylesheet`.
+    pub typed: bool,
+    /// Make class names unique to the stylesheet. Implies `typed`, whose
+    /// fields and `class_name` method are the way to refer to those classes.
+    pub scoped: bool,
+    /// The name given to the struct generated for `typed` or `scoped`, as in
+    /// `#[typed(Styles)]`. The macro then defines the struct instead of
+    /// returning a value, so it must be invoked where items are allowed.
+    pub name: Option<String>
+}
+
+/// Parses the options preceding the stylesheet into the given `Options`,
+/// returning the rest of the input. On errors, the options parsed so far
+/// are kept, so that the caller knows whether a struct was to be defined.
+pub fn parse(input: TokenStream, options: &mut Options) -> Result<TokenStream, Error> {
+    let mut tokens: Vec<TokenTree> = input.into_iter().collect();
+
+    loop {
+        let name = match (tokens.get(0), tokens.get(1)) {
+            (
+                Some(&TokenTree {
+                    kind: TokenNode::Op('#', _),
+                    ..
+                }),
+                Some(&TokenTree {
+                    kind: TokenNode::Group(Delimiter::Bracket, ref inner),
+                    span
+                })
+            ) => {
+                let inner: Vec<TokenTree> = inner.clone().into_iter().collect();
+                let name = match inner.get(0) {
+                    Some(&TokenTree {
+                        kind: TokenNode::Term(term),
+                        span
+                    }) if inner.len() <= 2 => (term.as_str().to_string(), span),
+                    _ => return Err(Error::new(span, "Expected a single css! option name"))
+                };
+                match inner.get(1) {
+                    Some(&TokenTree {
+                        kind: TokenNode::Group(Delimiter::Parenthesis, ref argument),
+                        span
+                    }) => {
+                        if name.0 != "typed" && name.0 != "scoped" {
+                            return Err(Error::new(span, format!("The css! option `{}` doesn't take a name", name.0)));
+                        }
+                        options.name = Some(struct_name(argument.clone(), span)?);
+                    }
+                    Some(other) => return Err(Error::new(other.span, "Expected a parenthesized struct name")),
+                    None => {}
+                }
+                name
+            }
+            _ => break
+        };
+
+        match name.0.as_str() {
+            "lenient" => options.lenient = true,
+            "themed" => options.themed = true,
+            "typed" => options.typed = true,
+            "scoped" => {
+                options.scoped = true;
+                options.typed = true;
+            }
+            other => return Err(Error::new(name.1, format!("Unknown css! option `{}`", other)))
+        }
+
+        if options.themed && options.typed {
+            return Err(Error::new(
+                name.1,
+                "The themed css! option can't be combined with typed or scoped"
+            ));
+        }
+
+        tokens.drain(..2);
+    }
+
+    Ok(tokens.into_iter().collect())
+}
+
+fn struct_name(argument: TokenStream, span: Span) -> Result<String, Error> {
+    let argument: Vec<TokenTree> = argument.into_iter().collect();
+    match argument.get(0) {
+        Some(&TokenTree {
+            kind: TokenNode::Term(term),
+            ..
+        }) if argument.len() == 1 => Ok(term.as_str().to_string()),
+        _ => Err(Error::new(span, "Expected a single struct name"))
+    }
+}
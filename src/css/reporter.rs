@@ -0,0 +1,43 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use rsx_primitives::rsx_stylesheet::servo_css_parser::types::{ContextualParseError, ParseErrorReporter, SourceLocation, UrlExtraData};
+use std::sync::Mutex;
+
+/// An invalid declaration or selector, located by its zero-based line and
+/// column in the CSS text that was handed to the parser.
+pub struct CssError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+#[derive(Default)]
+pub struct CollectingReporter {
+    errors: Mutex<Vec<CssError>>
+}
+
+impl CollectingReporter {
+    pub fn into_errors(self) -> Vec<CssError> {
+        self.errors.into_inner().unwrap()
+    }
+}
+
+impl ParseErrorReporter for CollectingReporter {
+    fn report_error(&self, _: &UrlExtraData, location: SourceLocation, error: ContextualParseError) {
+        // Lines are reported zero-based, but columns start at one.
+        self.errors.lock().unwrap().push(CssError {
+            line: location.line as usize,
+            column: (location.column as usize).saturating_sub(1),
+            message: error.to_string()
+        });
+    }
+}
@@ -0,0 +1,185 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+// Resolution of `var()` references, shared by the macro and the stylesheets
+// it generates for `#[themed]`, which include the text of this file. It can
+// only use the standard library.
+
+use std::collections::HashMap;
+
+/// What to do with references to undefined variables without a fallback.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Undefined {
+    Error,
+    /// Replace them with `initial`, for variables only provided at runtime.
+    Initial
+}
+
+/// If a comment or a string starts at the given byte index, returns the
+/// index just past its end. Their contents shouldn't be mistaken for
+/// syntax when scanning stylesheets.
+pub fn skip_opaque(css: &str, index: usize) -> Option<usize> {
+    if !css.is_char_boundary(index) {
+        return None;
+    }
+
+    let rest = &css[index..];
+    if rest.starts_with("/*") {
+        return Some(rest[2..].find("*/").map(|end| index + end + 4).unwrap_or(css.len()));
+    }
+
+    let quote = match rest.chars().next() {
+        Some(c) if c == '"' || c == '\'' => c,
+        _ => return None
+    };
+    let mut escaped = false;
+    for (offset, c) in rest.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == quote => return Some(index + offset + 1),
+            _ => {}
+        }
+    }
+    Some(css.len())
+}
+
+/// Resolves a value containing `var()` references, recursively.
+pub fn resolve(
+    value: &str,
+    variables: &HashMap<String, String>,
+    undefined: Undefined,
+    stack: &mut Vec<String>
+) -> Result<String, String> {
+    let mut result = String::new();
+    let mut last = 0;
+
+    for (start, end) in references(value) {
+        result.push_str(&value[last..start]);
+        last = end;
+
+        let (name, fallback) = parse_reference(&value[start..end])?;
+        if stack.contains(&name) {
+            return Err(format!("Circular reference to CSS variable {}", name));
+        }
+
+        let resolved = match variables.get(&name) {
+            Some(defined) => {
+                stack.push(name);
+                let resolved = resolve(defined, variables, undefined, stack)?;
+                stack.pop();
+                resolved
+            }
+            None => match fallback {
+                Some(fallback) => resolve(&fallback, variables, undefined, stack)?,
+                None if undefined == Undefined::Initial => "initial".to_string(),
+                None => return Err(format!("Undefined CSS variable {}", name))
+            }
+        };
+        result.push_str(&resolved);
+    }
+
+    result.push_str(&value[last..]);
+    Ok(result)
+}
+
+/// Splits the inside of a `var()` reference into the variable name and
+/// the optional fallback value.
+pub fn parse_reference(reference: &str) -> Result<(String, Option<String>), String> {
+    if !reference.ends_with(')') {
+        return Err("Unterminated var() reference".to_string());
+    }
+
+    let inner = &reference["var(".len()..reference.len() - 1];
+    let (name, fallback) = match top_level_comma(inner) {
+        Some(comma) => (&inner[..comma], Some(inner[comma + 1..].trim().to_string())),
+        None => (inner, None)
+    };
+
+    let name = name.trim();
+    if !name.starts_with("--") {
+        return Err(format!("Invalid CSS variable name {}", name));
+    }
+    Ok((name.to_string(), fallback))
+}
+
+/// Finds the byte ranges of the outermost `var()` references in the text.
+pub fn references(css: &str) -> Vec<(usize, usize)> {
+    let mut references = vec![];
+    let mut index = 0;
+
+    while index < css.len() {
+        if let Some(end) = skip_opaque(css, index) {
+            index = end;
+            continue;
+        }
+        let preceded_by_name = css[..index]
+            .chars()
+            .next_back()
+            .map(|c| c.is_alphanumeric() || c == '-' || c == '_')
+            .unwrap_or(false);
+        if !preceded_by_name && css[index..].starts_with("var(") {
+            let end = matching_paren(css, index + "var".len());
+            references.push((index, end));
+            index = end;
+            continue;
+        }
+        index += css[index..].chars().next().map(char::len_utf8).unwrap_or(1);
+    }
+
+    references
+}
+
+fn matching_paren(css: &str, open: usize) -> usize {
+    let mut depth = 0usize;
+    let mut index = open;
+
+    while index < css.len() {
+        if let Some(end) = skip_opaque(css, index) {
+            index = end;
+            continue;
+        }
+        match css.as_bytes()[index] {
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    css.len()
+}
+
+fn top_level_comma(css: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut index = 0;
+
+    while index < css.len() {
+        if let Some(end) = skip_opaque(css, index) {
+            index = end;
+            continue;
+        }
+        match css.as_bytes()[index] {
+            b'(' => depth += 1,
+            b')' => depth = depth.saturating_sub(1),
+            b',' if depth == 0 => return Some(index),
+            _ => {}
+        }
+        index += 1;
+    }
+
+    None
+}
@@ -0,0 +1,109 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use super::sheet::{Sheet, SheetSource};
+use super::typed::Field;
+use proc_macro::Span;
+use rsx_primitives::rsx_stylesheet::types::Stylesheet;
+use std::env;
+use std::path::Path;
+
+/// Identifies the stylesheet given to `css!`: the path of its file relative
+/// to the crate's manifest, or the location of the invocation for inline ones.
+/// Everything it imports shares the same scope.
+pub fn scope(root: &Sheet) -> String {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    match root.source {
+        SheetSource::File { ref path, .. } => path.strip_prefix(&manifest_dir)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .into_owned(),
+        SheetSource::Inline { .. } => {
+            let call_site = Span::call_site();
+            let source_file = call_site.source_file();
+            let start = call_site.start();
+            format!(
+                "{}:{}:{}",
+                Path::new(&*source_file.path()).to_string_lossy(),
+                start.line,
+                start.column
+            )
+        }
+    }
+}
+
+/// Makes a class name unique to the scope, such as `text` into `text_1b2c3d4e`.
+/// The hash is 32-bit FNV-1a, rather than the standard library's hasher, so
+/// that generated names don't change between Rust releases.
+pub fn scoped_class(scope: &str, class: &str) -> String {
+    let mut hash = 0x811c_9dc5u32;
+    let bytes = scope.bytes().chain(Some(0)).chain(class.bytes());
+    for byte in bytes {
+        hash ^= u32::from(byte);
+        hash = hash.wrapping_mul(0x0100_0193);
+    }
+    format!("{}_{:08x}", class, hash)
+}
+
+/// Rewrites every class in the stylesheet's selectors to its scoped name.
+pub fn rewrite(stylesheet: &mut Stylesheet, fields: &mut [Field], scope: &str) {
+    for rule in &mut stylesheet.0 {
+        for selector in &mut rule.selectors {
+            let rewritten = rewrite_selector(&selector.selector, scope);
+            selector.selector = rewritten.into();
+        }
+    }
+
+    for field in fields {
+        field.generated_class = scoped_class(scope, &field.class);
+    }
+}
+
+fn rewrite_selector(selector: &str, scope: &str) -> String {
+    let mut result = String::with_capacity(selector.len());
+    let mut chars = selector.chars().peekable();
+    let mut quote = None;
+    let mut brackets = 0usize;
+
+    while let Some(c) = chars.next() {
+        result.push(c);
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.next() {
+                    result.push(escaped);
+                }
+            }
+            _ if quote == Some(c) => quote = None,
+            _ if quote.is_some() => {}
+            '"' | '\'' => quote = Some(c),
+            '[' => brackets += 1,
+            ']' => brackets = brackets.saturating_sub(1),
+            '.' if brackets == 0 => {
+                let mut class = String::new();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '-' || next == '_') {
+                        break;
+                    }
+                    class.push(next);
+                    chars.next();
+                }
+                if class.is_empty() {
+                    continue;
+                }
+                let scoped = scoped_class(scope, &class);
+                result.push_str(&scoped);
+            }
+            _ => {}
+        }
+    }
+
+    result
+}
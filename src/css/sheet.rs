@@ -0,0 +1,110 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use diagnostics::Error;
+use proc_macro::Span;
+use source::{self, SourceMap};
+use std::path::PathBuf;
+
+pub enum SheetSource {
+    File { path: PathBuf, span: Span },
+    Inline { map: SourceMap }
+}
+
+struct Edit {
+    start: usize,
+    end: usize,
+    replaced_start: usize,
+    replaced_end: usize
+}
+
+/// The CSS text of a single stylesheet, either given inline to `css!` or
+/// loaded from a file, along with what's needed to report errors in it.
+pub struct Sheet {
+    pub css: String,
+    pub source: SheetSource,
+    original: String,
+    edits: Vec<Vec<Edit>>
+}
+
+impl Sheet {
+    pub fn new(css: String, source: SheetSource) -> Self {
+        Sheet {
+            original: css.clone(),
+            css,
+            source,
+            edits: vec![]
+        }
+    }
+
+    /// Replaces the given non-overlapping byte ranges of the text, keeping
+    /// track of them so that errors can still be reported at the offsets
+    /// they had in the original text.
+    pub fn replace(&mut self, mut replacements: Vec<(usize, usize, String)>) {
+        replacements.sort_by_key(|&(start, ..)| start);
+
+        let mut css = String::with_capacity(self.css.len());
+        let mut edits = vec![];
+        let mut last = 0;
+
+        for (start, end, replacement) in replacements {
+            css.push_str(&self.css[last..start]);
+            edits.push(Edit {
+                start: css.len(),
+                end: css.len() + replacement.len(),
+                replaced_start: start,
+                replaced_end: end
+            });
+            css.push_str(&replacement);
+            last = end;
+        }
+        css.push_str(&self.css[last..]);
+
+        self.css = css;
+        self.edits.push(edits);
+    }
+
+    fn original_offset(&self, offset: usize) -> usize {
+        self.edits.iter().rev().fold(offset, |offset, edits| {
+            let mut shift = 0isize;
+            for edit in edits {
+                if offset < edit.start {
+                    break;
+                }
+                if offset < edit.end {
+                    return edit.replaced_start;
+                }
+                shift = edit.end as isize - edit.replaced_end as isize;
+            }
+            (offset as isize - shift) as usize
+        })
+    }
+
+    /// Locates an error at the given byte offset in the current text. Errors
+    /// in inline sheets point at the offending tokens, while errors in files
+    /// point at the path given to `css!` and mention the line and column in
+    /// the file.
+    pub fn error_at<T: Into<String>>(&self, offset: usize, message: T) -> Error {
+        let offset = self.original_offset(offset);
+        match self.source {
+            SheetSource::File { ref path, span } => source::file_error(path, &self.original, offset, span, message),
+            SheetSource::Inline { ref map } => map.error_at(offset, message)
+        }
+    }
+
+    pub fn span_at(&self, offset: usize) -> Span {
+        let offset = self.original_offset(offset);
+        match self.source {
+            SheetSource::File { span, .. } => span,
+            SheetSource::Inline { ref map } => map.span_at(offset)
+        }
+    }
+}
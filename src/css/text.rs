@@ -0,0 +1,35 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+pub use super::resolver::skip_opaque;
+
+/// Replaces a range of the text with spaces, keeping newlines, so that
+/// offsets and line numbers past it stay the same.
+pub fn blank_out(css: &str, start: usize, end: usize) -> String {
+    let blank: String = css[start..end]
+        .bytes()
+        .map(|b| if b == b'\n' { '\n' } else { ' ' })
+        .collect();
+    format!("{}{}{}", &css[..start], blank, &css[end..])
+}
+
+/// Converts a zero-based line and column into a byte offset.
+pub fn offset_of(css: &str, line: usize, column: usize) -> usize {
+    let line_start: usize = css.split('\n').take(line).map(|line| line.len() + 1).sum();
+    let line_start = line_start.min(css.len());
+    let line_text = css[line_start..].split('\n').next().unwrap_or("");
+    let column_offset = line_text
+        .char_indices()
+        .nth(column)
+        .map(|(index, _)| index)
+        .unwrap_or(line_text.len());
+    line_start + column_offset
+}
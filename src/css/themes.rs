@@ -0,0 +1,113 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use super::sheet::Sheet;
+use super::vars::{self, Variables};
+use diagnostics::Error;
+use quote::Tokens;
+
+/// Generates a `ThemedStylesheet`, which keeps the `var()` references of
+/// the given sheets so that they can be substituted at runtime. Its `theme`
+/// method takes the values of some variables, like `&[("--fg", "white")]`,
+/// and parses the stylesheet they produce.
+///
+/// References are resolved at runtime against the given values first, then
+/// against the variables declared in the sheets, which are kept unresolved,
+/// so that `--a: var(--b)` follows a value given for `--b`. Given values can
+/// refer to other variables too. Variables without a value or a fallback
+/// become `initial`. Circular references and invalid CSS are returned as
+/// errors. The generated code includes the same resolver the macro uses, so
+/// both resolve references the same way.
+pub fn expand(sheets: &[Sheet], variables: &Variables) -> Result<Tokens, Error> {
+    let mut css = String::new();
+
+    for sheet in sheets {
+        for (start, end) in vars::references(&sheet.css) {
+            vars::parse_reference(&sheet.css[start..end]).map_err(|message| sheet.error_at(start, message))?;
+        }
+        css.push_str(&sheet.css);
+        css.push('\n');
+    }
+
+    let mut declared: Vec<(&String, &String)> = variables.iter().collect();
+    declared.sort();
+    let (names, values): (Vec<&String>, Vec<&String>) = declared.into_iter().unzip();
+
+    let mut resolver = Tokens::new();
+    resolver.append(include_str!("resolver.rs"));
+
+    Ok(quote! {
+        {
+            #[allow(dead_code)]
+            mod __rsx_resolver {
+                #resolver
+            }
+
+            struct ThemedStylesheet {
+                css: &'static str,
+                variables: &'static [(&'static str, &'static str)]
+            }
+
+            impl ThemedStylesheet {
+                #[allow(dead_code)]
+                fn theme(
+                    &self,
+                    variables: &[(&str, &str)]
+                ) -> Result<::rsx_primitives::rsx_stylesheet::types::Stylesheet, Vec<String>> {
+                    use ::rsx_primitives::rsx_stylesheet::servo_css_parser::parse_with_reporter;
+                    use ::rsx_primitives::rsx_stylesheet::servo_css_parser::types::*;
+                    use std::collections::HashMap;
+                    use std::sync::Mutex;
+
+                    struct Reporter(Mutex<Vec<String>>);
+
+                    impl ParseErrorReporter for Reporter {
+                        fn report_error(&self, _: &UrlExtraData, location: SourceLocation, error: ContextualParseError) {
+                            self.0.lock().unwrap().push(format!("{}:{}: {}", location.line + 1, location.column, error));
+                        }
+                    }
+
+                    // Given values come last, so they override declared ones.
+                    let variables: HashMap<String, String> = self.variables
+                        .iter()
+                        .chain(variables)
+                        .map(|&(name, value)| (name.to_string(), value.to_string()))
+                        .collect();
+                    let css = __rsx_resolver::resolve(self.css, &variables, __rsx_resolver::Undefined::Initial, &mut vec![])
+                        .map_err(|error| vec![error])?;
+
+                    let url = Url::parse("about::inline").unwrap();
+                    let reporter = Reporter(Mutex::new(vec![]));
+                    let stylesheet = parse_with_reporter(
+                        &css,
+                        url,
+                        Origin::UserAgent,
+                        QuirksMode::NoQuirks,
+                        MediaList::empty(),
+                        &reporter
+                    ).into();
+
+                    let errors = reporter.0.into_inner().unwrap();
+                    if errors.is_empty() {
+                        Ok(stylesheet)
+                    } else {
+                        Err(errors)
+                    }
+                }
+            }
+
+            ThemedStylesheet {
+                css: #css,
+                variables: &[#( (#names, #values) ),*]
+            }
+        }
+    })
+}
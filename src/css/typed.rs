@@ -0,0 +1,189 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use super::sheet::Sheet;
+use super::text;
+use diagnostics::Error;
+use proc_macro::Span;
+use quote::{Ident, Tokens};
+use rsx_primitives::rsx_stylesheet::types::Stylesheet;
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "alignof", "as", "become", "box", "break", "const", "continue", "crate", "do",
+    "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in", "let", "loop",
+    "macro", "match", "mod", "move", "mut", "offsetof", "override", "priv", "proc", "pub", "pure",
+    "ref", "return", "self", "sizeof", "static", "struct", "super", "trait", "true", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Returns the class name if the selector is made of a single class.
+pub fn class_name(selector: &str) -> Option<&str> {
+    if !selector.starts_with('.') {
+        return None;
+    }
+    let name = &selector[1..];
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Turns a class name into a field name, such as `bar-baz` into `bar_baz`.
+pub fn field_name(class: &str) -> String {
+    let mut field: String = class
+        .chars()
+        .map(|c| if c == '-' { '_' } else { c })
+        .collect();
+    if field.chars().next().map(|c| c.is_numeric()).unwrap_or(false) {
+        field.insert(0, '_');
+    }
+    if KEYWORDS.contains(&field.as_str()) {
+        field.push('_');
+    }
+    field
+}
+
+pub struct Field {
+    /// The class name as written in the stylesheet.
+    pub class: String,
+    /// The class name used in the generated stylesheet, which differs from
+    /// the original one for scoped stylesheets.
+    pub generated_class: String,
+    pub name: String
+}
+
+/// Lists the class names used as selectors in the stylesheet, in order of
+/// appearance, along with their field names.
+pub fn fields(stylesheet: &Stylesheet, sheets: &[Sheet]) -> Result<Vec<Field>, Error> {
+    let mut fields: Vec<Field> = vec![];
+
+    for rule in &stylesheet.0 {
+        for selector in &rule.selectors {
+            let selector: &str = &selector.selector;
+            let class = match class_name(selector) {
+                Some(class) => class,
+                None => continue
+            };
+            let name = field_name(class);
+            match fields.iter().find(|field| field.name == name) {
+                Some(field) if field.class != class => {
+                    let message = format!("Classes .{} and .{} both map to the field `{}`", field.class, class, name);
+                    return Err(locate(sheets, class, message));
+                }
+                Some(_) => {}
+                None => fields.push(Field {
+                    class: class.to_string(),
+                    generated_class: class.to_string(),
+                    name
+                })
+            }
+        }
+    }
+
+    Ok(fields)
+}
+
+/// Reports an error at the first selector using the given class.
+fn locate(sheets: &[Sheet], class: &str, message: String) -> Error {
+    for sheet in sheets {
+        let css = &sheet.css;
+        let mut index = 0;
+        while index < css.len() {
+            if let Some(end) = text::skip_opaque(css, index) {
+                index = end;
+                continue;
+            }
+            let rest = &css[index..];
+            if rest.starts_with('.') && rest[1..].starts_with(class) {
+                let followed_by_name = rest[1 + class.len()..]
+                    .chars()
+                    .next()
+                    .map(|c| c.is_alphanumeric() || c == '-' || c == '_')
+                    .unwrap_or(false);
+                if !followed_by_name {
+                    return sheet.error_at(index, message);
+                }
+            }
+            index += rest.chars().next().map(char::len_utf8).unwrap_or(1);
+        }
+    }
+    Error::new(Span::call_site(), message)
+}
+
+/// Generates a struct with one field per class selector, holding its styles,
+/// so that misspelled class names are compile errors. The styles are looked
+/// up at compile time, so its `new` function only builds them. Its
+/// `class_name` method maps class names as written in the stylesheet to the
+/// ones it actually uses, and its `stylesheet` method returns the stylesheet
+/// with those names, to register it wherever styles are matched by class.
+///
+/// Without a name, the struct is defined in a block evaluating to its value,
+/// so it can't be named by callers.
+pub fn expand(stylesheet: &mut Stylesheet, fields: &[Field], name: Option<&str>, dependencies: &Tokens) -> Tokens {
+    let struct_name = Ident::new(name.unwrap_or("Styles"));
+    let names: Vec<Ident> = fields.iter().map(|field| Ident::new(field.name.as_str())).collect();
+    let names_again = names.clone();
+    let values: Vec<Tokens> = fields
+        .iter()
+        .map(|field| {
+            let declarations = stylesheet.get(&format!(".{}", field.generated_class));
+            quote! { vec![#(#declarations),*] }
+        })
+        .collect();
+    let classes: Vec<&str> = fields.iter().map(|field| field.class.as_str()).collect();
+    let generated_classes: Vec<&str> = fields
+        .iter()
+        .map(|field| field.generated_class.as_str())
+        .collect();
+    let stylesheet = &*stylesheet;
+
+    let definition = quote! {
+        #[allow(dead_code)]
+        pub struct #struct_name {
+            #( pub #names: Vec<::rsx_primitives::rsx_stylesheet::types::StyleDeclaration> ),*
+        }
+
+        #[allow(dead_code)]
+        impl #struct_name {
+            pub fn new() -> Self {
+                #dependencies
+                #struct_name {
+                    #( #names_again: #values ),*
+                }
+            }
+
+            pub fn class_name(&self, class: &str) -> Option<&'static str> {
+                match class {
+                    #( #classes => Some(#generated_classes), )*
+                    _ => None
+                }
+            }
+
+            pub fn stylesheet(&self) -> ::rsx_primitives::rsx_stylesheet::types::Stylesheet {
+                #stylesheet
+            }
+        }
+    };
+
+    if name.is_some() {
+        return definition;
+    }
+
+    quote! {
+        {
+            #definition
+            #struct_name::new()
+        }
+    }
+}
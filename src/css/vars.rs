@@ -0,0 +1,167 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+pub use super::resolver::{parse_reference, references, resolve, Undefined};
+use super::sheet::Sheet;
+use super::text;
+use diagnostics::Error;
+use std::collections::HashMap;
+
+/// Custom properties declared in `:root` rules, across all loaded sheets.
+/// Later declarations override earlier ones, following the cascade.
+pub type Variables = HashMap<String, String>;
+
+struct Rule {
+    prelude: String,
+    start: usize,
+    body_start: usize,
+    end: usize
+}
+
+struct Declaration {
+    name: String,
+    value: String,
+    start: usize,
+    end: usize
+}
+
+/// Collects the custom properties declared in `:root` rules and removes
+/// them from the sheets, along with rules left empty by doing so.
+pub fn extract(sheets: &mut [Sheet]) -> Variables {
+    let mut variables = Variables::new();
+
+    for sheet in sheets.iter_mut() {
+        for rule in top_level_rules(&sheet.css) {
+            if rule.prelude.trim() != ":root" {
+                continue;
+            }
+
+            let declarations = declarations(&sheet.css, rule.body_start, rule.end - 1);
+            let custom = declarations
+                .iter()
+                .filter(|declaration| declaration.name.starts_with("--"))
+                .count();
+            if custom == 0 {
+                continue;
+            }
+
+            for declaration in &declarations {
+                if declaration.name.starts_with("--") {
+                    variables.insert(declaration.name.clone(), declaration.value.clone());
+                    if custom != declarations.len() {
+                        sheet.css = text::blank_out(&sheet.css, declaration.start, declaration.end);
+                    }
+                }
+            }
+            if custom == declarations.len() {
+                sheet.css = text::blank_out(&sheet.css, rule.start, rule.end);
+            }
+        }
+    }
+
+    variables
+}
+
+/// Replaces every `var(--name)` and `var(--name, fallback)` reference in
+/// the sheet with the value of the variable, or with the fallback if it isn't
+/// defined.
+pub fn substitute(sheet: &mut Sheet, variables: &Variables, undefined: Undefined) -> Result<(), Error> {
+    let mut replacements = vec![];
+
+    for reference in references(&sheet.css) {
+        let value = resolve(&sheet.css[reference.0..reference.1], variables, undefined, &mut vec![])
+            .map_err(|message| sheet.error_at(reference.0, message))?;
+        replacements.push((reference.0, reference.1, value));
+    }
+
+    if !replacements.is_empty() {
+        sheet.replace(replacements);
+    }
+    Ok(())
+}
+
+fn top_level_rules(css: &str) -> Vec<Rule> {
+    let mut rules = vec![];
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut body_start = 0;
+    let mut index = 0;
+
+    while index < css.len() {
+        if let Some(end) = text::skip_opaque(css, index) {
+            index = end;
+            continue;
+        }
+        match css.as_bytes()[index] {
+            b'{' => {
+                if depth == 0 {
+                    body_start = index + 1;
+                }
+                depth += 1;
+            }
+            b'}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    rules.push(Rule {
+                        prelude: css[start..body_start - 1].to_string(),
+                        start,
+                        body_start,
+                        end: index + 1
+                    });
+                    start = index + 1;
+                }
+            }
+            b';' if depth == 0 => start = index + 1,
+            _ => {}
+        }
+        index += 1;
+    }
+
+    rules
+}
+
+fn declarations(css: &str, start: usize, end: usize) -> Vec<Declaration> {
+    let mut declarations = vec![];
+    let mut depth = 0usize;
+    let mut declaration_start = start;
+    let mut index = start;
+
+    while index <= end {
+        if index < end {
+            if let Some(skipped) = text::skip_opaque(css, index) {
+                index = skipped;
+                continue;
+            }
+        }
+        let byte = if index < end { css.as_bytes()[index] } else { b';' };
+        match byte {
+            b'(' | b'{' | b'[' => depth += 1,
+            b')' | b'}' | b']' => depth = depth.saturating_sub(1),
+            b';' if depth == 0 => {
+                let declaration_end = (index + 1).min(end);
+                let declaration = &css[declaration_start..index.min(end)];
+                if let Some(colon) = declaration.find(':') {
+                    declarations.push(Declaration {
+                        name: declaration[..colon].trim().to_string(),
+                        value: declaration[colon + 1..].trim().to_string(),
+                        start: declaration_start,
+                        end: declaration_end
+                    });
+                }
+                declaration_start = index + 1;
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+
+    declarations
+}
@@ -0,0 +1,46 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use proc_macro::{Span, TokenStream};
+
+pub struct Error {
+    pub span: Span,
+    pub message: String
+}
+
+impl Error {
+    pub fn new<T: Into<String>>(span: Span, message: T) -> Self {
+        Error {
+            span,
+            message: message.into()
+        }
+    }
+
+    pub fn emit(self) -> TokenStream {
+        error(self.span, self.message)
+    }
+}
+
+/// Emits a compile error at the given span and returns a placeholder expansion.
+pub fn error<T: Into<String>>(span: Span, message: T) -> TokenStream {
+    span.error(message).emit();
+    placeholder()
+}
+
+/// An expansion which type checks anywhere, returned after emitting errors
+/// so that they aren't followed by unrelated ones.
+pub fn placeholder() -> TokenStream {
+    "unreachable!()".parse().unwrap()
+}
+
+pub fn warning<T: Into<String>>(span: Span, message: T) {
+    span.warning(message).emit();
+}
@@ -0,0 +1,146 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use diagnostics::Error;
+use proc_macro::{Span, TokenNode, TokenStream, TokenTree};
+use quote::Tokens;
+use std::env;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// If the macro input is a single string literal, like `"style.css"` or
+/// `r"style.css"`, returns its value and span: macros given one load that
+/// file instead of treating the input as inline source.
+pub fn literal_path(input: &TokenStream) -> Option<(String, Span)> {
+    let tokens: Vec<TokenTree> = input.clone().into_iter().collect();
+    let (literal, span) = match tokens.get(0) {
+        Some(&TokenTree {
+            kind: TokenNode::Literal(ref literal),
+            span
+        }) if tokens.len() == 1 => (literal.to_string(), span),
+        _ => return None
+    };
+
+    if literal.starts_with('"') {
+        return Some((unescape(&literal[1..literal.len() - 1]), span));
+    }
+    if literal.starts_with('r') {
+        let hashes = literal[1..].chars().take_while(|&c| c == '#').count();
+        let quoted = &literal[1 + hashes..literal.len() - hashes];
+        if quoted.starts_with('"') {
+            return Some((quoted[1..quoted.len() - 1].to_string(), span));
+        }
+    }
+    None
+}
+
+/// Processes the escapes of a string literal's contents. The compiler has
+/// already rejected invalid ones.
+fn unescape(contents: &str) -> String {
+    let mut result = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => result.push('\n'),
+            Some('r') => result.push('\r'),
+            Some('t') => result.push('\t'),
+            Some('0') => result.push('\0'),
+            Some('x') => {
+                let code: String = chars.by_ref().take(2).collect();
+                result.extend(u8::from_str_radix(&code, 16).ok().map(char::from));
+            }
+            Some('u') => {
+                let code: String = chars.by_ref().skip(1).take_while(|&c| c != '}').collect();
+                result.extend(u32::from_str_radix(&code, 16).ok().and_then(::std::char::from_u32));
+            }
+            // A backslash at the end of a line skips the line break and the
+            // indentation following it.
+            Some('\n') => while chars.peek().map_or(false, |c| c.is_whitespace()) {
+                chars.next();
+            },
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+
+    result
+}
+
+/// Resolves a path given to a macro against the invoking crate's manifest
+/// directory, then against the directory of the source file containing the
+/// invocation. The process working directory isn't used, since it varies
+/// between workspaces, cargo invocations and IDE expansions.
+pub fn resolve(path: &str, span: Span) -> Result<PathBuf, Error> {
+    let mut attempted = vec![];
+
+    if let Ok(manifest_dir) = env::var("CARGO_MANIFEST_DIR") {
+        attempted.push(Path::new(&manifest_dir).join(path));
+    }
+
+    let source_file = span.source_file();
+    if source_file.is_real() {
+        let source_path = Path::new(&*source_file.path()).to_path_buf();
+        let source_path = match env::current_dir() {
+            Ok(current_dir) => current_dir.join(source_path),
+            Err(_) => source_path
+        };
+        if let Some(source_dir) = source_path.parent() {
+            attempted.push(source_dir.join(path));
+        }
+    }
+
+    if let Some(found) = attempted.iter().find(|candidate| candidate.is_file()) {
+        return Ok(found.clone());
+    }
+
+    let attempted = attempted
+        .iter()
+        .map(|candidate| format!("\n    {}", candidate.to_string_lossy()))
+        .collect::<String>();
+
+    Err(Error::new(
+        span,
+        format!("Couldn't find file {}, tried:{}", path, attempted)
+    ))
+}
+
+/// Cargo only knows about files reachable from the crate's sources, so
+/// files read by a macro must also be included by its expansion for
+/// edits to them to trigger a rebuild.
+pub fn track<P: AsRef<Path>>(paths: &[P]) -> Tokens {
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| path.as_ref().to_string_lossy().into_owned())
+        .collect();
+
+    quote! {
+        #( let _ = include_bytes!(#paths); )*
+    }
+}
+
+pub fn read(path: &Path, span: Span) -> Result<String, Error> {
+    let mut result = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut result))
+        .map_err(|err| {
+            Error::new(
+                span,
+                format!("Couldn't read file {}: {}", path.to_string_lossy(), err)
+            )
+        })?;
+    Ok(result)
+}
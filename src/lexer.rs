@@ -0,0 +1,185 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+/// A byte range of the text, or the offset and description of the first
+/// thing which can't be tokenized.
+pub type Lexed = Result<Vec<(usize, usize)>, (usize, String)>;
+
+/// Splits text read from a file into the tokens the compiler would produce
+/// for it, were it written inline, returning their byte ranges in order.
+/// Whitespace and comments are skipped, and punctuation is split into single
+/// characters, like the `Op` tokens of a token stream.
+pub fn tokenize(source: &str) -> Lexed {
+    let mut tokens = vec![];
+    let mut delimiters: Vec<(char, usize)> = vec![];
+    let mut index = 0;
+
+    while let Some(c) = source[index..].chars().next() {
+        let start = index;
+        let rest = &source[index..];
+
+        if c.is_whitespace() {
+            index += c.len_utf8();
+            continue;
+        }
+        if rest.starts_with("//") {
+            index = rest.find('\n').map_or(source.len(), |end| start + end);
+            continue;
+        }
+        if rest.starts_with("/*") {
+            index = block_comment_end(source, start).ok_or((start, "Unterminated block comment".to_string()))?;
+            continue;
+        }
+
+        index = match c {
+            '(' | '[' | '{' => {
+                delimiters.push((c, start));
+                start + 1
+            }
+            ')' | ']' | '}' => {
+                match delimiters.pop() {
+                    Some((open, _)) if closing(open) == c => {}
+                    Some((open, _)) => {
+                        return Err((start, format!("Expected `{}` but found `{}`", closing(open), c)));
+                    }
+                    None => return Err((start, format!("Unexpected `{}`", c)))
+                }
+                start + 1
+            }
+            '"' => string_end(source, start + 1, '"').ok_or((start, "Unterminated string".to_string()))?,
+            '\'' => quote_end(source, start)?,
+            'r' | 'b' if is_string_prefix(rest) => prefixed_string_end(source, start)?,
+            _ if c.is_alphabetic() || c == '_' => identifier_end(source, start),
+            _ if c.is_numeric() => number_end(source, start),
+            _ if (c as u32) < 0x80 && !c.is_control() => start + 1,
+            _ => return Err((start, format!("Unexpected character `{}`", c)))
+        };
+        tokens.push((start, index));
+    }
+
+    match delimiters.pop() {
+        Some((open, offset)) => Err((offset, format!("Unclosed `{}`", open))),
+        None => Ok(tokens)
+    }
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '(' => ')',
+        '[' => ']',
+        _ => '}'
+    }
+}
+
+/// Block comments nest, unlike in most languages.
+fn block_comment_end(source: &str, start: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut index = start;
+    while index < source.len() {
+        let rest = &source[index..];
+        if rest.starts_with("/*") {
+            depth += 1;
+            index += 2;
+        } else if rest.starts_with("*/") {
+            depth -= 1;
+            index += 2;
+            if depth == 0 {
+                return Some(index);
+            }
+        } else {
+            index += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    None
+}
+
+/// Returns the offset past the closing quote of a string whose contents
+/// start at the given offset.
+fn string_end(source: &str, from: usize, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (offset, c) in source[from..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            _ if c == quote => return Some(from + offset + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A quote starts either a character literal, like `'a'` or `'\n'`, or a
+/// lifetime, like `'a`.
+fn quote_end(source: &str, start: usize) -> Result<usize, (usize, String)> {
+    let mut chars = source[start + 1..].char_indices();
+    match chars.next() {
+        Some((_, '\\')) => string_end(source, start + 1, '\'').ok_or((start, "Unterminated character literal".to_string())),
+        Some((_, c)) => match chars.next() {
+            Some((offset, '\'')) => Ok(start + 1 + offset + 1),
+            _ if c.is_alphabetic() || c == '_' => Ok(identifier_end(source, start + 1)),
+            _ => Err((start, "Unterminated character literal".to_string()))
+        },
+        None => Err((start, "Unterminated character literal".to_string()))
+    }
+}
+
+fn is_string_prefix(rest: &str) -> bool {
+    ["b\"", "b'", "br\"", "br#", "r\"", "r#"]
+        .iter()
+        .any(|prefix| rest.starts_with(prefix))
+}
+
+/// Byte strings, byte characters and raw strings, like `b"a"`, `b'a'` or
+/// `r#"a"#`.
+fn prefixed_string_end(source: &str, start: usize) -> Result<usize, (usize, String)> {
+    let unterminated = || (start, "Unterminated string".to_string());
+    let mut index = start;
+    if source[index..].starts_with('b') {
+        index += 1;
+    }
+    if source[index..].starts_with('\'') {
+        return string_end(source, index + 1, '\'').ok_or_else(unterminated);
+    }
+    if !source[index..].starts_with('r') {
+        return string_end(source, index + 1, '"').ok_or_else(unterminated);
+    }
+
+    index += 1;
+    let hashes = source[index..].chars().take_while(|&c| c == '#').count();
+    index += hashes;
+    if !source[index..].starts_with('"') {
+        // Something like `r#foo`, which isn't a string after all.
+        return Ok(identifier_end(source, start));
+    }
+    let terminator = format!("\"{}", "#".repeat(hashes));
+    source[index + 1..]
+        .find(&terminator)
+        .map(|end| index + 1 + end + terminator.len())
+        .ok_or_else(unterminated)
+}
+
+fn identifier_end(source: &str, start: usize) -> usize {
+    source[start..]
+        .char_indices()
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+        .map_or(source.len(), |(offset, _)| start + offset)
+}
+
+/// Numbers include their suffix, like `10px`, and their fractional part,
+/// but not a following range, like in `0..10`.
+fn number_end(source: &str, start: usize) -> usize {
+    let mut index = identifier_end(source, start);
+    let rest = &source[index..];
+    if rest.starts_with('.') && rest[1..].chars().next().map_or(false, char::is_numeric) {
+        index = identifier_end(source, index + 1);
+    }
+    index
+}
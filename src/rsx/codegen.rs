@@ -0,0 +1,626 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use diagnostics::Error;
+use proc_macro::{Span, TokenNode, TokenStream, TokenTree};
+use quote::{Ident, Tokens};
+use rsx_primitives::rsx_dom::types::*;
+use super::{fragments, regions, spread};
+use source::SourceMap;
+
+const SPAN_MARKER: &str = "__rsx_span_";
+
+/// Generates the expression building an element at runtime. Most of the
+/// tree tokenizes itself, except for components, which are elements whose
+/// name is capitalized, like `<Dropdown show={true}>...</Dropdown>`. These
+/// expand to `Dropdown::props().show(true).build().render(children)`, using
+/// the builder generated by `#[derive(Props)]`, so that missing, unknown or
+/// mistyped attributes are compile errors.
+///
+/// The generated code is plain text, so tokens which should report errors
+/// at a template location are preceded by a marker identifier, replaced by
+/// the corresponding span in `respan`.
+pub struct Codegen<'a> {
+    map: &'a SourceMap,
+    cursor: usize,
+    spans: Vec<Span>,
+    dynamic_children: bool
+}
+
+impl<'a> Codegen<'a> {
+    pub fn new(map: &'a SourceMap) -> Self {
+        Codegen {
+            map,
+            cursor: 0,
+            spans: vec![],
+            dynamic_children: false
+        }
+    }
+
+    /// Items the generated code relies on, to be placed in the same block.
+    ///
+    /// Child code blocks are pushed through a method implemented at several
+    /// levels of references, so that method resolution picks the most
+    /// specific one without specialization: unit values push nothing,
+    /// iterators (including options) push one child per item, and anything
+    /// else is a single child.
+    pub fn prelude(&self) -> Tokens {
+        if !self.dynamic_children {
+            return Tokens::new();
+        }
+        quote! {
+            struct __RSXValue<T>(::std::cell::Cell<Option<T>>);
+
+            trait __RSXPushUnit {
+                fn __rsx_push(&self, children: &mut Vec<RSXChild>);
+            }
+
+            impl<'a, 'b> __RSXPushUnit for &'a &'b __RSXValue<()> {
+                fn __rsx_push(&self, _: &mut Vec<RSXChild>) {}
+            }
+
+            trait __RSXPushIterator {
+                fn __rsx_push(&self, children: &mut Vec<RSXChild>);
+            }
+
+            impl<'a, T> __RSXPushIterator for &'a __RSXValue<T>
+            where
+                T: IntoIterator,
+                T::Item: 'static
+            {
+                fn __rsx_push(&self, children: &mut Vec<RSXChild>) {
+                    for item in self.0.take().unwrap() {
+                        children.push(RSXChild::CodeBlock(ArbitraryCodeRegion::RuntimeValue(
+                            ComputedValue(Box::new(item))
+                        )));
+                    }
+                }
+            }
+
+            trait __RSXPushValue {
+                fn __rsx_push(&self, children: &mut Vec<RSXChild>);
+            }
+
+            impl<T: 'static> __RSXPushValue for __RSXValue<T> {
+                fn __rsx_push(&self, children: &mut Vec<RSXChild>) {
+                    children.push(RSXChild::CodeBlock(ArbitraryCodeRegion::RuntimeValue(
+                        ComputedValue(Box::new(self.0.take().unwrap()))
+                    )));
+                }
+            }
+        }
+    }
+
+    pub fn element(&mut self, element: &RSXElement) -> Result<Tokens, Error> {
+        if fragments::is_fragment(element) {
+            // The fragment's name was inserted between the `<` and `>` tokens.
+            let offset = self.map.find_tokens(&["<", ">"], self.cursor).unwrap_or(self.cursor);
+            return Err(self.map.error_at(
+                offset,
+                "Fragments can only be used as the root or as children of an element"
+            ));
+        }
+        self.open_tag(element_name(element));
+        match *element {
+            RSXElement::Normal(RSXNormalElement(ref name, ref attributes, ref children)) => {
+                if let Some(component) = component_name(name) {
+                    return self.component_call(component, attributes, children);
+                }
+                self.check_name(name)?;
+                self.check_attribute_components(attributes)?;
+                let attributes = self.attributes(attributes)?;
+                let children = self.children(children)?;
+                Ok(quote! {
+                    RSXElement::Normal(RSXNormalElement(#name, #attributes, #children))
+                })
+            }
+            RSXElement::SelfClosing(RSXSelfClosingElement(ref name, ref attributes)) => {
+                if let Some(component) = component_name(name) {
+                    return self.component_call(component, attributes, &RSXChildren(vec![]));
+                }
+                self.check_name(name)?;
+                self.check_attribute_components(attributes)?;
+                let attributes = self.attributes(attributes)?;
+                Ok(quote! {
+                    RSXElement::SelfClosing(RSXSelfClosingElement(#name, #attributes))
+                })
+            }
+        }
+    }
+
+    /// Generates the expression building a root fragment, which is a vector
+    /// of its top level elements and code blocks, each converted with `into`.
+    pub fn fragment(&mut self, fragment: &RSXElement) -> Result<Tokens, Error> {
+        let mut children = vec![];
+        if let RSXElement::Normal(RSXNormalElement(_, _, ref contents)) = *fragment {
+            flatten(contents, &mut children);
+        }
+
+        let mut items = vec![];
+        let mut keys = vec![];
+        for child in children {
+            items.push(match *child {
+                RSXChild::Element(ref element) => {
+                    self.check_key(element, &mut keys)?;
+                    self.element(element)?
+                }
+                RSXChild::CodeBlock(ref region) => self.code_block(region)?,
+                ref text => {
+                    let offset = match *text {
+                        RSXChild::Text(RSXText(ref text)) => text
+                            .split_whitespace()
+                            .next()
+                            .map_or(self.cursor, |word| self.offset_of(word)),
+                        _ => self.cursor
+                    };
+                    return Err(self.map.error_at(
+                        offset,
+                        "Text at the root of a fragment must be wrapped in an element"
+                    ));
+                }
+            });
+        }
+
+        Ok(quote! {
+            vec![#(#items.into()),*]
+        })
+    }
+
+    /// Replaces the span markers in the expansion with the spans they refer to.
+    pub fn respan(&self, stream: TokenStream) -> TokenStream {
+        let mut trees = vec![];
+        let mut pending = None;
+
+        for TokenTree { span, kind } in stream {
+            if let TokenNode::Term(term) = kind {
+                if let Some(index) = marker_index(term.as_str()) {
+                    pending = Some(self.spans[index]);
+                    continue;
+                }
+            }
+            let kind = match kind {
+                TokenNode::Group(delimiter, inner) => TokenNode::Group(delimiter, self.respan(inner)),
+                kind => kind
+            };
+            trees.push(match pending.take() {
+                Some(span) => with_span(TokenTree { span, kind }, span),
+                None => TokenTree { span, kind }
+            });
+        }
+
+        trees.into_iter().collect()
+    }
+
+    fn children(&mut self, children: &RSXChildren) -> Result<Tokens, Error> {
+        let mut flattened = vec![];
+        flatten(children, &mut flattened);
+
+        let dynamic = flattened.iter().any(|child| match **child {
+            RSXChild::CodeBlock(_) => true,
+            _ => false
+        });
+
+        let mut tokens = vec![];
+        let mut keys = vec![];
+        for child in flattened {
+            let child = match *child {
+                RSXChild::Element(ref child) => {
+                    self.check_key(child, &mut keys)?;
+                    let child = self.element(child)?;
+                    quote! { RSXChild::Element(#child) }
+                }
+                RSXChild::CodeBlock(ref region) if dynamic => {
+                    let value = self.code_block(region)?;
+                    tokens.push(quote! {
+                        (&&&__RSXValue(::std::cell::Cell::new(Some(#value)))).__rsx_push(&mut __rsx_children);
+                    });
+                    continue;
+                }
+                _ => quote! { #child }
+            };
+            tokens.push(if dynamic {
+                quote! { __rsx_children.push(#child); }
+            } else {
+                child
+            });
+        }
+
+        if !dynamic {
+            return Ok(quote! {
+                RSXChildren(vec![#(#tokens),*])
+            });
+        }
+
+        // Code blocks may evaluate to several children, so the list is
+        // built at runtime.
+        self.dynamic_children = true;
+        Ok(quote! {
+            RSXChildren({
+                let mut __rsx_children: Vec<RSXChild> = vec![];
+                #(#tokens)*
+                __rsx_children
+            })
+        })
+    }
+
+    /// Rejects siblings sharing the same literal `key` attribute, since keys
+    /// identify children across renders. Keys computed in code blocks, and
+    /// those of elements produced by iterators, can't be compared here.
+    fn check_key(&self, element: &RSXElement, keys: &mut Vec<String>) -> Result<(), Error> {
+        let key = match literal_key(element) {
+            Some(key) => key,
+            None => return Ok(())
+        };
+        if keys.contains(&key) {
+            let tag = self.next_tag(element_name(element)).unwrap_or(self.cursor);
+            let offset = self.map
+                .find("key", tag)
+                .map(|(end, _)| end - "key".len())
+                .unwrap_or(tag);
+            return Err(self.map.error_at(offset, format!("Duplicate key {} among siblings", key)));
+        }
+        keys.push(key);
+        Ok(())
+    }
+
+    /// Capitalized names are reserved for components, which have to be
+    /// named by a single identifier, unlike `<Foo:bar>` or `<Foo.Bar>`.
+    fn check_name(&self, name: &RSXElementName) -> Result<(), Error> {
+        match capitalized_name(name) {
+            Some(name) => Err(self.map.error_at(
+                self.offset_of(&name),
+                format!("Element {} can't be expanded as a component, since it isn't a single identifier", name)
+            )),
+            None => Ok(())
+        }
+    }
+
+    /// Attribute values of elements other than components tokenize
+    /// themselves, including the elements and code blocks they hold, so
+    /// components can't be expanded there, and would otherwise silently
+    /// become plain elements.
+    fn check_attribute_components(&self, attributes: &RSXAttributes) -> Result<(), Error> {
+        for attribute in &attributes.0 {
+            if let RSXAttribute::Named(_, ref value) = *attribute {
+                if let Some(component) = value_component(value) {
+                    return Err(self.map.error_at(
+                        self.offset_of(&component),
+                        format!("Component {} can't be expanded in an attribute of an element", component)
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The offset of the next token spelled `text`, or the cursor if there's
+    /// none, like for names spanning several tokens.
+    fn offset_of(&self, text: &str) -> usize {
+        self.map
+            .find(text, self.cursor)
+            .map(|(end, _)| end - text.len())
+            .unwrap_or(self.cursor)
+    }
+
+    fn component_call(
+        &mut self,
+        name: &str,
+        attributes: &RSXAttributes,
+        children: &RSXChildren
+    ) -> Result<Tokens, Error> {
+        let component = self.marker(name);
+        let component_offset = self.cursor;
+        let mut setters = vec![];
+
+        for attribute in &attributes.0 {
+            if spread::is_spread(attribute) {
+                return Err(self.map.error_at(
+                    component_offset,
+                    format!("Component {} doesn't accept spread attributes", name)
+                ));
+            }
+            match *attribute {
+                RSXAttribute::Named(RSXAttributeName::Name(RSXIdentifier(ref field)), ref value) => {
+                    let field: &str = field;
+                    let marker = self.marker(field);
+                    if !is_identifier(field) {
+                        return Err(self.map.error_at(
+                            self.cursor,
+                            format!("Attribute {} of component {} isn't a valid field name", field, name)
+                        ));
+                    }
+                    let field = Ident::new(field);
+                    let value = self.attribute_value(value)?;
+                    setters.push(quote! {
+                        .#marker #field #marker (#value)
+                    });
+                }
+                _ => {
+                    return Err(self.map.error_at(
+                        component_offset,
+                        format!("Component {} only accepts simply named attributes", name)
+                    ));
+                }
+            }
+        }
+
+        let name = Ident::new(name);
+        let children = self.children(children)?;
+
+        Ok(quote! {
+            {
+                #component #name::props()
+                    #(#setters)*
+                    .#component build()
+                    .render(#children)
+            }
+        })
+    }
+
+    fn attribute_value(&mut self, value: &RSXAttributeValue) -> Result<Tokens, Error> {
+        Ok(match *value {
+            RSXAttributeValue::Default => quote! { true },
+            RSXAttributeValue::CodeBlock(ref region) => self.code_block(region)?,
+            RSXAttributeValue::Element(ref value) => self.element(value)?,
+            ref literal => {
+                // Strings, numbers and booleans are written the same way in
+                // RSX and Rust, and converted into whatever the field holds.
+                let mut tokens = Tokens::new();
+                tokens.append(literal.to_string());
+                quote! { (#tokens).into() }
+            }
+        })
+    }
+
+    /// Attributes tokenize themselves, unless they contain spread attributes
+    /// like `{...common}`, whose expression yields `Attribute`s. These are
+    /// merged in order at runtime, later attributes replacing earlier ones
+    /// with the same name.
+    fn attributes(&mut self, attributes: &RSXAttributes) -> Result<Tokens, Error> {
+        if !attributes.0.iter().any(spread::is_spread) {
+            return Ok(quote! { #attributes });
+        }
+
+        // Generated names are prefixed so that they can't shadow anything used
+        // by the spread expressions.
+        let mut pushes = vec![];
+        for attribute in &attributes.0 {
+            pushes.push(match *attribute {
+                RSXAttribute::Named(_, RSXAttributeValue::CodeBlock(ref region)) if spread::is_spread(attribute) => {
+                    let spread = self.code_block(region)?;
+                    quote! {
+                        __rsx_attributes.extend(#spread.into_iter().map(|Attribute(name, value)| {
+                            RSXAttribute::Named(
+                                RSXAttributeName::Name(RSXIdentifier(name.to_string().into())),
+                                RSXAttributeValue::CodeBlock(ArbitraryCodeRegion::RuntimeValue(
+                                    ComputedValue(Box::new(value))
+                                ))
+                            )
+                        }));
+                    }
+                }
+                _ => quote! { __rsx_attributes.push(#attribute); }
+            });
+        }
+
+        Ok(quote! {
+            RSXAttributes({
+                #[allow(unreachable_patterns)]
+                fn __rsx_attribute_name(attribute: &RSXAttribute) -> String {
+                    match *attribute {
+                        RSXAttribute::Named(ref name, _) => name.to_string(),
+                        _ => String::new()
+                    }
+                }
+
+                let mut __rsx_attributes: Vec<RSXAttribute> = vec![];
+                #(#pushes)*
+
+                let mut __rsx_merged: Vec<RSXAttribute> = vec![];
+                for attribute in __rsx_attributes {
+                    let name = __rsx_attribute_name(&attribute);
+                    __rsx_merged.retain(|existing| __rsx_attribute_name(existing) != name);
+                    __rsx_merged.push(attribute);
+                }
+                __rsx_merged
+            })
+        })
+    }
+
+    /// The Rust code of a code block, which the parser splits into tokens
+    /// and the RSX elements written among them. Elements are generated like
+    /// any other, so components and dynamic children work inside code blocks.
+    fn code_block(&mut self, region: &ArbitraryCodeRegion) -> Result<Tokens, Error> {
+        let mut tokens = vec![];
+        for fragment in regions::fragments(region) {
+            tokens.push(match *fragment {
+                RSXRawCodeFragment::ParsedRSXElement(ref element) => self.element(element)?,
+                RSXRawCodeFragment::Empty => continue,
+                ref code => quote! { #code }
+            });
+        }
+        Ok(quote! {
+            { #(#tokens)* }
+        })
+    }
+
+    /// Returns the offset of the next opening tag of an element with the
+    /// given name, or of its first segment for names like `<foo:bar>`.
+    fn next_tag(&self, name: &RSXElementName) -> Option<usize> {
+        let name = name.to_string();
+        let first = name.split(|c: char| !(c.is_alphanumeric() || c == '_')).next().unwrap_or("");
+        self.map.find_tokens(&["<", first], self.cursor)
+    }
+
+    /// Moves the cursor past the opening `<` of the element being generated,
+    /// so that errors about it are searched for from there. Elements are
+    /// generated in template order, so the next such tag is the right one.
+    fn open_tag(&mut self, name: &RSXElementName) {
+        if let Some(offset) = self.next_tag(name) {
+            self.cursor = offset + 1;
+        }
+    }
+
+    /// Finds the next occurrence of `text` in the template and returns a
+    /// marker for its span, for the same reason searching forward from the
+    /// previous match finds the right token.
+    fn marker(&mut self, text: &str) -> Ident {
+        let span = match self.map.find(text, self.cursor) {
+            Some((end, span)) => {
+                self.cursor = end;
+                span
+            }
+            None => self.map.span_at(self.cursor)
+        };
+        self.spans.push(span);
+        Ident::new(format!("{}{}", SPAN_MARKER, self.spans.len() - 1))
+    }
+}
+
+/// Splices the children of nested fragments into their parent's children.
+fn flatten<'a>(children: &'a RSXChildren, flattened: &mut Vec<&'a RSXChild>) {
+    for child in &children.0 {
+        if let RSXChild::Element(ref element) = *child {
+            if fragments::is_fragment(element) {
+                if let RSXElement::Normal(RSXNormalElement(_, _, ref inner)) = *element {
+                    flatten(inner, flattened);
+                }
+                continue;
+            }
+        }
+        flattened.push(child);
+    }
+}
+
+fn element_name(element: &RSXElement) -> &RSXElementName {
+    match *element {
+        RSXElement::Normal(RSXNormalElement(ref name, ..)) |
+        RSXElement::SelfClosing(RSXSelfClosingElement(ref name, ..)) => name
+    }
+}
+
+/// The value of the element's `key` attribute, if it's a literal.
+fn literal_key(element: &RSXElement) -> Option<String> {
+    let attributes = match *element {
+        RSXElement::Normal(RSXNormalElement(_, ref attributes, _)) |
+        RSXElement::SelfClosing(RSXSelfClosingElement(_, ref attributes)) => attributes
+    };
+    attributes
+        .0
+        .iter()
+        .filter_map(|attribute| match *attribute {
+            RSXAttribute::Named(RSXAttributeName::Name(RSXIdentifier(ref name)), ref value) => {
+                let name: &str = name;
+                match *value {
+                    RSXAttributeValue::Default |
+                    RSXAttributeValue::CodeBlock(_) |
+                    RSXAttributeValue::Element(_) => None,
+                    ref value if name == "key" => Some(value.to_string()),
+                    _ => None
+                }
+            }
+            _ => None
+        })
+        .next()
+}
+
+fn component_name(name: &RSXElementName) -> Option<&str> {
+    match *name {
+        RSXElementName::Name(RSXIdentifier(ref name)) => {
+            let name: &str = name;
+            if name.chars().next().map(char::is_uppercase).unwrap_or(false) {
+                Some(name)
+            } else {
+                None
+            }
+        }
+        _ => None
+    }
+}
+
+fn capitalized_name(name: &RSXElementName) -> Option<String> {
+    let name = name.to_string();
+    if name.chars().next().map(char::is_uppercase).unwrap_or(false) {
+        Some(name)
+    } else {
+        None
+    }
+}
+
+/// Returns the name of the first component found in the element, its
+/// attribute values or its children, including the element itself.
+fn first_component(element: &RSXElement) -> Option<String> {
+    let (name, attributes, children) = match *element {
+        RSXElement::Normal(RSXNormalElement(ref name, ref attributes, ref children)) => {
+            (name, attributes, Some(children))
+        }
+        RSXElement::SelfClosing(RSXSelfClosingElement(ref name, ref attributes)) => (name, attributes, None)
+    };
+    if let Some(name) = capitalized_name(name) {
+        return Some(name);
+    }
+
+    let in_attributes = attributes
+        .0
+        .iter()
+        .filter_map(|attribute| match *attribute {
+            RSXAttribute::Named(_, ref value) => value_component(value),
+            _ => None
+        })
+        .next();
+    if in_attributes.is_some() {
+        return in_attributes;
+    }
+
+    children
+        .into_iter()
+        .flat_map(|children| &children.0)
+        .filter_map(|child| match *child {
+            RSXChild::Element(ref element) => first_component(element),
+            RSXChild::CodeBlock(ref region) => region_component(region),
+            _ => None
+        })
+        .next()
+}
+
+fn value_component(value: &RSXAttributeValue) -> Option<String> {
+    match *value {
+        RSXAttributeValue::Element(ref element) => first_component(element),
+        RSXAttributeValue::CodeBlock(ref region) => region_component(region),
+        _ => None
+    }
+}
+
+fn region_component(region: &ArbitraryCodeRegion) -> Option<String> {
+    regions::elements(region).into_iter().filter_map(first_component).next()
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn marker_index(term: &str) -> Option<usize> {
+    if term.starts_with(SPAN_MARKER) {
+        term[SPAN_MARKER.len()..].parse().ok()
+    } else {
+        None
+    }
+}
+
+fn with_span(tree: TokenTree, span: Span) -> TokenTree {
+    let kind = match tree.kind {
+        TokenNode::Group(delimiter, inner) => {
+            TokenNode::Group(delimiter, inner.into_iter().map(|tree| with_span(tree, span)).collect())
+        }
+        kind => kind
+    };
+    TokenTree { span, kind }
+}
@@ -0,0 +1,65 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use diagnostics::Error;
+use rsx_primitives::rsx_dom::types::*;
+use source::SourceMap;
+
+/// The element name fragments are parsed as. Nothing stops templates from
+/// spelling it, so `desugar` rejects templates which do.
+const NAME: &str = "__rsx_fragment";
+
+/// Rewrites `<>` and `</>` into a sentinel element the parser understands.
+/// These are matched on tokens, so string literals in code blocks are left
+/// alone.
+pub fn desugar(map: &mut SourceMap) -> Result<(), Error> {
+    let mut offsets = vec![];
+    {
+        let tokens = map.tokens();
+        for (index, &(start, text)) in tokens.iter().enumerate() {
+            if text == NAME {
+                return Err(map.error_at(start, format!("The name {} is reserved for fragments", NAME)));
+            }
+            if text != "<" {
+                continue;
+            }
+            let next = tokens.get(index + 1).map(|&(_, text)| text);
+            let after = tokens.get(index + 2).map(|&(_, text)| text);
+            match (next, after) {
+                (Some(">"), _) => offsets.push(start + 1),
+                (Some("/"), Some(">")) => offsets.push(tokens[index + 1].0 + 1),
+                _ => {}
+            }
+        }
+    }
+    for offset in offsets.into_iter().rev() {
+        map.insert(offset, NAME);
+    }
+    Ok(())
+}
+
+/// Wraps the whole template into a fragment, for templates with several roots.
+pub fn wrap(map: &mut SourceMap) {
+    let end = map.source.len();
+    map.insert(end, &format!("</{}>", NAME));
+    map.insert(0, &format!("<{}>", NAME));
+}
+
+pub fn is_fragment(element: &RSXElement) -> bool {
+    match *element {
+        RSXElement::Normal(RSXNormalElement(RSXElementName::Name(RSXIdentifier(ref name)), ..)) |
+        RSXElement::SelfClosing(RSXSelfClosingElement(RSXElementName::Name(RSXIdentifier(ref name)), ..)) => {
+            let name: &str = name;
+            name == NAME
+        }
+        _ => false
+    }
+}
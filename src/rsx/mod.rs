@@ -0,0 +1,124 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+mod codegen;
+mod fragments;
+mod props;
+mod regions;
+mod spread;
+mod whitespace;
+
+pub use self::props::derive as derive_props;
+
+use self::codegen::Codegen;
+use diagnostics::Error;
+use files;
+use proc_macro::TokenStream;
+use quote::Tokens;
+use rsx_primitives::rsx_dom::rsx_parser::parse as parse_rsx;
+use rsx_primitives::rsx_dom::types::RSXElement;
+use source::SourceMap;
+
+pub fn expand(input: TokenStream) -> TokenStream {
+    let (mut map, dependencies) = match load(input) {
+        Ok(loaded) => loaded,
+        Err(err) => return err.emit()
+    };
+    if let Err(err) = fragments::desugar(&mut map) {
+        return err.emit();
+    }
+    spread::desugar(&mut map);
+
+    let mut parsed = match parse(&map) {
+        Ok((parsed, None)) => parsed,
+        Ok((_, Some(_))) => {
+            // Several root elements are parsed as the children of a fragment.
+            fragments::wrap(&mut map);
+            match parse(&map) {
+                Ok((parsed, None)) => parsed,
+                Ok((_, Some(offset))) => {
+                    return map.error_at(offset, "Unexpected tokens after the root element").emit();
+                }
+                Err(err) => return err.emit()
+            }
+        }
+        Err(err) => return err.emit()
+    };
+
+    whitespace::collapse(&mut parsed);
+
+    let mut codegen = Codegen::new(&map);
+    let expanded = if fragments::is_fragment(&parsed) {
+        codegen.fragment(&parsed)
+    } else {
+        codegen.element(&parsed).map(|element| {
+            quote! {
+                #element.into()
+            }
+        })
+    };
+
+    let expanded = match expanded {
+        Ok(expanded) => expanded,
+        Err(err) => return err.emit()
+    };
+
+    let prelude = codegen.prelude();
+    let expanded = quote! {
+        {
+            #dependencies
+            #prelude
+            #expanded
+        }
+    };
+
+    codegen.respan(expanded.parse().unwrap())
+}
+
+/// Maps the template, which is either written inline, or read from the
+/// file at the given path, like `rsx!("template.rsx")`. Code blocks in
+/// files are expanded at the call site, so they resolve against its scope.
+fn load(input: TokenStream) -> Result<(SourceMap, Tokens), Error> {
+    if let Some((path, span)) = files::literal_path(&input) {
+        let path = files::resolve(&path, span)?;
+        let template = files::read(&path, span)?;
+        let dependencies = files::track(&[&path]);
+        return Ok((SourceMap::from_file(template, path, span)?, dependencies));
+    }
+
+    Ok((SourceMap::from_tokens(input), Tokens::new()))
+}
+
+/// Parses the template, returning the root element along with the offset
+/// of anything left after it.
+fn parse(map: &SourceMap) -> Result<(RSXElement, Option<usize>), Error> {
+    let source = map.source.trim_right();
+
+    match parse_rsx(source) {
+        Ok((parsed, rest)) => {
+            let rest = rest.trim_left();
+            if rest.is_empty() {
+                Ok((parsed, None))
+            } else {
+                Ok((parsed, Some(source.len() - rest.len())))
+            }
+        }
+        Err(err) => {
+            let offset = err.position.translate_position(source);
+            let message = err.errors
+                .iter()
+                .map(|error| error.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Err(map.error_at(offset, format!("Malformed RSX: {}", message)))
+        }
+    }
+}
@@ -0,0 +1,193 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use proc_macro::{Span, TokenStream};
+use quote::Tokens;
+use syn::{self, Body, DeriveInput, Ident, MetaItem, NestedMetaItem, Ty, VariantData};
+
+struct Prop {
+    name: Ident,
+    ty: Ty,
+    param: Ident,
+    default: bool
+}
+
+/// Implements `#[derive(Props)]`, which turns a struct into a component's
+/// props through a builder whose type tracks the fields set so far:
+///
+/// - `Card::props()` returns a `CardPropsBuilder` with every field unset,
+/// - each field has a setter, only available while the field is unset,
+/// - `build()` is only available once every field without a
+///   `#[prop(default)]` attribute has been set.
+///
+/// This is what `rsx!` calls for capitalized elements, so that missing,
+/// duplicate, unknown and mistyped attributes fail to compile.
+pub fn derive(input: TokenStream) -> TokenStream {
+    let expanded = syn::parse_derive_input(&input.to_string())
+        .and_then(|input| expand(&input));
+
+    match expanded {
+        Ok(expanded) => expanded.parse().unwrap(),
+        Err(message) => {
+            Span::call_site().error(message).emit();
+            TokenStream::empty()
+        }
+    }
+}
+
+fn expand(input: &DeriveInput) -> Result<Tokens, String> {
+    let name = &input.ident;
+    let vis = &input.vis;
+
+    if !input.generics.lifetimes.is_empty() || !input.generics.ty_params.is_empty() {
+        return Err(format!("#[derive(Props)] doesn't support generic structs like {}", name));
+    }
+
+    let props = match input.body {
+        Body::Struct(VariantData::Struct(ref fields)) => fields
+            .iter()
+            .map(|field| {
+                let name = field.ident.clone().unwrap();
+                Ok(Prop {
+                    param: Ident::new(format!("__{}", name)),
+                    default: has_default(&field.attrs, &name)?,
+                    ty: field.ty.clone(),
+                    name
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?,
+        _ => return Err(format!("#[derive(Props)] requires {} to be a struct with named fields", name))
+    };
+
+    let builder = Ident::new(format!("{}PropsBuilder", name));
+    let default = Ident::new(format!("{}PropsDefault", name));
+
+    let fields = &props.iter().map(|prop| prop.name.clone()).collect::<Vec<_>>()[..];
+    let params = &props.iter().map(|prop| prop.param.clone()).collect::<Vec<_>>()[..];
+    let unset = &props.iter().map(|_| quote! { () }).collect::<Vec<_>>()[..];
+
+    let setters = props.iter().enumerate().map(|(index, prop)| {
+        let field = &prop.name;
+        let ty = &prop.ty;
+        let other_params = params.iter().enumerate().filter(|&(i, _)| i != index).map(|(_, p)| p);
+        let (before, after): (Vec<_>, Vec<_>) = params
+            .iter()
+            .enumerate()
+            .map(|(i, param)| {
+                if i == index {
+                    (quote! { () }, quote! { (#ty,) })
+                } else {
+                    (quote! { #param }, quote! { #param })
+                }
+            })
+            .unzip();
+        let values = fields.iter().map(|other| {
+            if other == field {
+                quote! { #other: (value,) }
+            } else {
+                quote! { #other: self.#other }
+            }
+        });
+
+        quote! {
+            #[allow(dead_code)]
+            impl<#(#other_params),*> #builder<#(#before),*> {
+                #vis fn #field(self, value: #ty) -> #builder<#(#after),*> {
+                    #builder { #(#values),* }
+                }
+            }
+        }
+    });
+
+    let optional_params = props.iter().filter(|prop| prop.default).map(|prop| &prop.param);
+    let bounds = props.iter().filter(|prop| prop.default).map(|prop| {
+        let (param, ty) = (&prop.param, &prop.ty);
+        quote! { #param: #default<#ty> }
+    });
+    let built = props.iter().map(|prop| {
+        let (param, ty) = (&prop.param, &prop.ty);
+        if prop.default {
+            quote! { #param }
+        } else {
+            quote! { (#ty,) }
+        }
+    });
+    let values = props.iter().map(|prop| {
+        let (field, ty) = (&prop.name, &prop.ty);
+        if prop.default {
+            quote! { #field: #default::<#ty>::value(self.#field) }
+        } else {
+            quote! { #field: self.#field.0 }
+        }
+    });
+
+    Ok(quote! {
+        #[doc(hidden)]
+        #[allow(dead_code, non_camel_case_types)]
+        #vis struct #builder<#(#params),*> {
+            #(#fields: #params),*
+        }
+
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        #vis trait #default<T> {
+            fn value(self) -> T;
+        }
+
+        impl<T: Default> #default<T> for () {
+            fn value(self) -> T {
+                T::default()
+            }
+        }
+
+        impl<T> #default<T> for (T,) {
+            fn value(self) -> T {
+                self.0
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #name {
+            #vis fn props() -> #builder<#(#unset),*> {
+                #builder { #(#fields: ()),* }
+            }
+        }
+
+        #(#setters)*
+
+        #[allow(dead_code, non_camel_case_types)]
+        impl<#(#optional_params),*> #builder<#(#built),*> where #(#bounds),* {
+            #vis fn build(self) -> #name {
+                #name { #(#values),* }
+            }
+        }
+    })
+}
+
+fn has_default(attrs: &[syn::Attribute], field: &Ident) -> Result<bool, String> {
+    let mut default = false;
+    for attr in attrs {
+        if let MetaItem::List(ref name, ref items) = attr.value {
+            if name != "prop" {
+                continue;
+            }
+            for item in items {
+                match *item {
+                    NestedMetaItem::MetaItem(MetaItem::Word(ref word)) if word == "default" => {
+                        default = true;
+                    }
+                    _ => return Err(format!("Unknown #[prop] option on field {}", field))
+                }
+            }
+        }
+    }
+    Ok(default)
+}
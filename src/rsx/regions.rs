@@ -0,0 +1,48 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use rsx_primitives::rsx_dom::types::*;
+
+/// Returns what the parser split a code block into: Rust tokens, and the
+/// RSX elements written among them.
+pub fn fragments(region: &ArbitraryCodeRegion) -> &[RSXRawCodeFragment] {
+    match *region {
+        ArbitraryCodeRegion::SourceCode(RSXParsedExpression { ref tokens, .. }) => tokens,
+        _ => &[]
+    }
+}
+
+/// Returns the RSX elements written inside a code block, like the `<li>` in
+/// `{ items.iter().map(|item| <li>{ item }</li>) }`.
+pub fn elements(region: &ArbitraryCodeRegion) -> Vec<&RSXElement> {
+    fragments(region)
+        .iter()
+        .filter_map(|fragment| match *fragment {
+            RSXRawCodeFragment::ParsedRSXElement(ref element) => Some(element),
+            _ => None
+        })
+        .collect()
+}
+
+/// Same as `elements`, for rewriting them. Code blocks are parsed into Rust
+/// tokens interleaved with the elements they contain.
+pub fn elements_mut(region: &mut ArbitraryCodeRegion) -> Vec<&mut RSXElement> {
+    match *region {
+        ArbitraryCodeRegion::SourceCode(RSXParsedExpression { ref mut tokens, .. }) => tokens
+            .iter_mut()
+            .filter_map(|fragment| match *fragment {
+                RSXRawCodeFragment::ParsedRSXElement(ref mut element) => Some(element),
+                _ => None
+            })
+            .collect(),
+        _ => vec![]
+    }
+}
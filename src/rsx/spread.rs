@@ -0,0 +1,58 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use rsx_primitives::rsx_dom::types::*;
+use source::SourceMap;
+
+/// The attribute name spread attributes are parsed as.
+const NAME: &str = "__rsx_spread";
+
+/// Rewrites spread attributes like `{...attributes}` into an attribute with
+/// a sentinel name whose value is the spread expression, which the parser
+/// understands. Only braces directly inside a tag are considered, so code
+/// blocks containing ranges or spreads are left alone.
+pub fn desugar(map: &mut SourceMap) {
+    let mut spreads = vec![];
+    {
+        let tokens = map.tokens();
+        let texts: Vec<&str> = tokens.iter().map(|&(_, text)| text).collect();
+        let (mut depth, mut in_tag) = (0usize, false);
+
+        for (index, &(start, text)) in tokens.iter().enumerate() {
+            match text {
+                "{" => {
+                    if depth == 0 && in_tag && texts.get(index + 1..index + 4) == Some(&[".", ".", "."][..]) {
+                        spreads.push((start, tokens[index + 1].0, tokens[index + 3].0 + 1));
+                    }
+                    depth += 1;
+                }
+                "}" => depth = depth.saturating_sub(1),
+                "<" if depth == 0 => in_tag = true,
+                ">" if depth == 0 => in_tag = false,
+                _ => {}
+            }
+        }
+    }
+    for (brace, dots_start, dots_end) in spreads.into_iter().rev() {
+        map.blank(dots_start, dots_end);
+        map.insert(brace, &format!("{}=", NAME));
+    }
+}
+
+pub fn is_spread(attribute: &RSXAttribute) -> bool {
+    match *attribute {
+        RSXAttribute::Named(RSXAttributeName::Name(RSXIdentifier(ref name)), _) => {
+            let name: &str = name;
+            name == NAME
+        }
+        _ => false
+    }
+}
@@ -0,0 +1,92 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use super::regions;
+use rsx_primitives::rsx_dom::types::*;
+
+/// Applies JSX whitespace semantics to every text child in the tree: lines
+/// are trimmed, lines containing only whitespace are removed, and the
+/// remaining ones are joined by a single space. Text left empty is dropped
+/// entirely. Explicit spacers like `{" "}` are code blocks, so they survive.
+/// Elements nested in code blocks and attribute values are collapsed too.
+pub fn collapse(element: &mut RSXElement) {
+    match *element {
+        RSXElement::Normal(RSXNormalElement(_, ref mut attributes, RSXChildren(ref mut children))) => {
+            collapse_attributes(attributes);
+
+            let mut collapsed = Vec::with_capacity(children.len());
+            for mut child in children.drain(..) {
+                match child {
+                    RSXChild::Text(RSXText(ref contents)) => {
+                        let contents = collapse_text(contents);
+                        if !contents.is_empty() {
+                            collapsed.push(RSXChild::Text(RSXText(contents.into())));
+                        }
+                        continue;
+                    }
+                    RSXChild::Element(ref mut element) => collapse(element),
+                    RSXChild::CodeBlock(ref mut region) => collapse_region(region),
+                    _ => {}
+                }
+                collapsed.push(child);
+            }
+            *children = collapsed;
+        }
+        RSXElement::SelfClosing(RSXSelfClosingElement(_, ref mut attributes)) => {
+            collapse_attributes(attributes);
+        }
+    }
+}
+
+fn collapse_attributes(attributes: &mut RSXAttributes) {
+    for attribute in &mut attributes.0 {
+        if let RSXAttribute::Named(_, ref mut value) = *attribute {
+            match *value {
+                RSXAttributeValue::Element(ref mut element) => collapse(element),
+                RSXAttributeValue::CodeBlock(ref mut region) => collapse_region(region),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn collapse_region(region: &mut ArbitraryCodeRegion) {
+    for element in regions::elements_mut(region) {
+        collapse(element);
+    }
+}
+
+fn collapse_text(text: &str) -> String {
+    // Unlike `str::lines`, splitting keeps a trailing empty line, so that
+    // the last line with text gets its end trimmed too.
+    let lines: Vec<&str> = text.split('\n').map(|line| line.trim_right_matches('\r')).collect();
+    let last_non_empty = lines.iter().rposition(|line| !line.trim().is_empty());
+
+    let mut result = String::new();
+    for (index, line) in lines.iter().enumerate() {
+        let mut line = &line[..];
+        if index != 0 {
+            line = line.trim_left();
+        }
+        if index != lines.len() - 1 {
+            line = line.trim_right();
+        }
+        if line.is_empty() {
+            continue;
+        }
+        result.push_str(line);
+        if Some(index) != last_non_empty {
+            result.push(' ');
+        }
+    }
+
+    result
+}
@@ -0,0 +1,304 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+use diagnostics::Error;
+#[cfg(feature = "rsx-macro")]
+use lexer;
+use proc_macro::{Delimiter, LineColumn, Spacing, Span, TokenNode, TokenStream};
+use std::path::{Path, PathBuf};
+
+struct MappedToken {
+    start: usize,
+    end: usize,
+    span: Span
+}
+
+/// The text handed to the parsers, along with the span of every token
+/// it was built from, so that offsets into the text can be reported
+/// against the original macro input.
+///
+/// Token streams don't carry whitespace, so it's rebuilt from the token
+/// positions, falling back to a single space between tokens whose spans
+/// can't be compared (like those created by other macros).
+///
+/// Maps can also be built from a file, in which case every token maps to
+/// the span of the path given to the macro, and errors mention the line
+/// and column in the file instead.
+pub struct SourceMap {
+    pub source: String,
+    tokens: Vec<MappedToken>,
+    call_site: Span,
+    file: Option<PathBuf>,
+    insertions: Vec<(usize, usize)>,
+    last_end: Option<LineColumn>,
+    joint: bool
+}
+
+impl SourceMap {
+    pub fn from_tokens(input: TokenStream) -> Self {
+        let mut map = SourceMap {
+            source: String::new(),
+            tokens: vec![],
+            call_site: Span::call_site(),
+            file: None,
+            insertions: vec![],
+            last_end: None,
+            joint: false
+        };
+        map.push_stream(input);
+        map
+    }
+
+    /// Maps the contents of a file, tokenizing it the way it would be if it
+    /// were written inline, so that token based rewrites work the same way.
+    /// Text which doesn't tokenize is an error, located in the file.
+    /// Comments are blanked out, since the compiler drops them from inline
+    /// templates.
+    #[cfg(feature = "rsx-macro")]
+    pub fn from_file(source: String, path: PathBuf, span: Span) -> Result<Self, Error> {
+        let tokens = lexer::tokenize(&source);
+        let source = match tokens {
+            Ok(ref tokens) => blank_comments(&source, tokens),
+            Err(_) => source
+        };
+        let mut map = SourceMap {
+            source,
+            tokens: vec![],
+            call_site: span,
+            file: Some(path),
+            insertions: vec![],
+            last_end: None,
+            joint: false
+        };
+        match tokens {
+            Ok(tokens) => {
+                map.tokens = tokens
+                    .into_iter()
+                    .map(|(start, end)| MappedToken { start, end, span })
+                    .collect();
+                Ok(map)
+            }
+            Err((offset, message)) => Err(map.error_at(offset, format!("Invalid template: {}", message)))
+        }
+    }
+
+    /// Returns the span of the token containing the given byte offset.
+    /// Offsets falling between tokens resolve to the following token,
+    /// and offsets past the end resolve to the last one.
+    pub fn span_at(&self, offset: usize) -> Span {
+        self.tokens
+            .iter()
+            .filter(|token| token.start <= offset && offset < token.end)
+            .min_by_key(|token| token.end - token.start)
+            .or_else(|| self.tokens.iter().find(|token| token.start >= offset))
+            .or_else(|| self.tokens.last())
+            .map(|token| token.span)
+            .unwrap_or(self.call_site)
+    }
+
+    /// Creates an error at the given byte offset.
+    pub fn error_at<T: Into<String>>(&self, offset: usize, message: T) -> Error {
+        match self.file {
+            Some(ref path) => file_error(path, &self.source, self.original_offset(offset), self.call_site, message),
+            None => Error::new(self.span_at(offset), message)
+        }
+    }
+
+    /// Returns the end offset and span of the first token spelled `text`
+    /// starting at or after the given byte offset.
+    #[cfg(feature = "rsx-macro")]
+    pub fn find(&self, text: &str, from: usize) -> Option<(usize, Span)> {
+        self.tokens
+            .iter()
+            .find(|token| token.start >= from && &self.source[token.start..token.end] == text)
+            .map(|token| (token.end, token.span))
+    }
+
+    /// Returns the offset of the first run of consecutive tokens spelled
+    /// like `texts`, starting at or after the given byte offset.
+    #[cfg(feature = "rsx-macro")]
+    pub fn find_tokens(&self, texts: &[&str], from: usize) -> Option<usize> {
+        self.tokens
+            .windows(texts.len())
+            .find(|window| {
+                window[0].start >= from && window
+                    .iter()
+                    .zip(texts)
+                    .all(|(token, &text)| &self.source[token.start..token.end] == text)
+            })
+            .map(|window| window[0].start)
+    }
+
+    /// Returns the offset and text of every token, in source order.
+    #[cfg(feature = "rsx-macro")]
+    pub fn tokens(&self) -> Vec<(usize, &str)> {
+        self.tokens
+            .iter()
+            .map(|token| (token.start, &self.source[token.start..token.end]))
+            .collect()
+    }
+
+    /// Inserts text which wasn't part of the macro input, such as desugared
+    /// syntax. Offsets inside it resolve to the token that follows.
+    #[cfg(feature = "rsx-macro")]
+    pub fn insert(&mut self, offset: usize, text: &str) {
+        self.source.insert_str(offset, text);
+        self.insertions.push((offset, text.len()));
+        for token in &mut self.tokens {
+            if token.start >= offset {
+                token.start += text.len();
+                token.end += text.len();
+            }
+        }
+    }
+
+    /// Replaces the given range with spaces, keeping offsets unchanged.
+    #[cfg(feature = "rsx-macro")]
+    pub fn blank(&mut self, start: usize, end: usize) {
+        let spaces = " ".repeat(self.source[start..end].len());
+        self.source = [&self.source[..start], &spaces, &self.source[end..]].concat();
+    }
+
+    /// Converts an offset in the source into one in the text it was built
+    /// from, before any insertions.
+    fn original_offset(&self, mut offset: usize) -> usize {
+        for &(start, len) in self.insertions.iter().rev() {
+            if offset >= start + len {
+                offset -= len;
+            } else if offset > start {
+                offset = start;
+            }
+        }
+        offset
+    }
+
+    fn push_stream(&mut self, stream: TokenStream) {
+        for tree in stream {
+            let span = tree.span;
+            match tree.kind {
+                TokenNode::Group(delimiter, inner) => {
+                    let (open, close) = match delimiter {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => {
+                            self.push_stream(inner);
+                            continue;
+                        }
+                    };
+                    let (start, end) = (span.start(), span.end());
+                    let open_end = LineColumn {
+                        line: start.line,
+                        column: start.column + 1
+                    };
+                    let close_start = LineColumn {
+                        line: end.line,
+                        column: end.column.saturating_sub(1)
+                    };
+                    self.push_text(open, span, start, open_end);
+                    self.push_stream(inner);
+                    self.push_text(close, span, close_start, end);
+                }
+                TokenNode::Term(term) => {
+                    self.push_text(term.as_str(), span, span.start(), span.end());
+                }
+                TokenNode::Literal(literal) => {
+                    self.push_text(&literal.to_string(), span, span.start(), span.end());
+                }
+                TokenNode::Op(op, spacing) => {
+                    self.push_text(&op.to_string(), span, span.start(), span.end());
+                    self.joint = spacing == Spacing::Joint;
+                }
+            }
+        }
+    }
+
+    fn push_text(&mut self, text: &str, span: Span, start: LineColumn, end: LineColumn) {
+        self.push_whitespace(start);
+        let offset = self.source.len();
+        self.source.push_str(text);
+        self.tokens.push(MappedToken {
+            start: offset,
+            end: self.source.len(),
+            span
+        });
+        self.last_end = Some(end);
+        self.joint = false;
+    }
+
+    fn push_whitespace(&mut self, next_start: LineColumn) {
+        let last_end = match self.last_end {
+            Some(last_end) if !self.joint => last_end,
+            _ => return
+        };
+
+        if next_start.line == last_end.line && next_start.column >= last_end.column {
+            let spaces = next_start.column - last_end.column;
+            self.source.extend((0..spaces).map(|_| ' '));
+            return;
+        }
+        if next_start.line > last_end.line {
+            let newlines = next_start.line - last_end.line;
+            self.source.extend((0..newlines).map(|_| '\n'));
+            self.source.extend((0..next_start.column).map(|_| ' '));
+            return;
+        }
+
+        self.source.push(' ');
+    }
+}
+
+/// Replaces everything between tokens with whitespace, keeping newlines so
+/// that lines and columns stay the same.
+#[cfg(feature = "rsx-macro")]
+fn blank_comments(source: &str, tokens: &[(usize, usize)]) -> String {
+    let mut result = String::with_capacity(source.len());
+    let mut last = 0;
+    let end_of_file = (source.len(), source.len());
+
+    for &(start, end) in tokens.iter().chain(Some(&end_of_file)) {
+        for c in source[last..start].chars() {
+            if c == '\n' {
+                result.push('\n');
+            } else {
+                result.extend((0..c.len_utf8()).map(|_| ' '));
+            }
+        }
+        result.push_str(&source[start..end]);
+        last = end;
+    }
+    result
+}
+
+/// Creates an error at the given span for a byte offset into the text of a
+/// file, prefixing the message with the path, line and column, since spans
+/// can only point into the invoking source file.
+pub fn file_error<T: Into<String>>(path: &Path, source: &str, offset: usize, span: Span, message: T) -> Error {
+    let (line, column) = line_column(source, offset);
+    Error::new(
+        span,
+        format!(
+            "{}:{}:{}: {}",
+            path.to_string_lossy(),
+            line + 1,
+            column + 1,
+            message.into()
+        )
+    )
+}
+
+/// Converts a byte offset into a zero-based line and column.
+fn line_column(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    (line, before[line_start..].chars().count())
+}
@@ -0,0 +1,51 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+#![feature(proc_macro)]
+
+extern crate rsx;
+extern crate rsx_primitives;
+
+use rsx::css;
+
+
+// Errors in a named struct's stylesheet aren't followed by errors about the
+// expansion not being an item.
+css! {
+    #[typed(Collision)]
+    .bar-baz {
+        padding: 10px;
+    }
+    .bar_baz { //~ ERROR Classes .bar-baz and .bar_baz both map to the field `bar_baz`
+        margin: 10px;
+    }
+}
+
+css! {
+    #[scoped(Invalid)]
+    .foo {
+        bogus: 1px; //~ ERROR Unsupported property declaration
+    }
+}
+
+css! {
+    #[typed(Missing)]
+    "missing.css" //~ ERROR Couldn't find file missing.css
+}
+
+css! {
+    #[typed(Themed)] #[themed] //~ ERROR The themed css! option can't be combined with typed or scoped
+    .foo {
+        padding: 10px;
+    }
+}
+
+fn main() {}
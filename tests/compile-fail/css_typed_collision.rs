@@ -0,0 +1,29 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+#![feature(proc_macro)]
+
+extern crate rsx;
+extern crate rsx_primitives;
+
+use rsx::css;
+
+fn main() {
+    let _ = css! {
+        #[typed]
+        .bar-baz {
+            padding: 10px;
+        }
+        .bar_baz { //~ ERROR Classes .bar-baz and .bar_baz both map to the field `bar_baz`
+            margin: 10px;
+        }
+    };
+}
@@ -0,0 +1,27 @@
+/*
+Copyright 2016 Mozilla
+Licensed under the Apache License, Version 2.0 (the "License"); you may not use
+this file except in compliance with the License. You may obtain a copy of the
+License at http://www.apache.org/licenses/LICENSE-2.0
+Unless required by applicable law or agreed to in writing, software distributed
+under the License is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR
+CONDITIONS OF ANY KIND, either express or implied. See the License for the
+specific language governing permissions and limitations under the License.
+*/
+
+#![cfg(any(feature = "rsx-macro", feature = "css-macro"))]
+
+extern crate compiletest_rs as compiletest;
+
+use std::path::PathBuf;
+
+#[test]
+fn test_compile_fail() {
+    let mut config = compiletest::Config::default();
+    config.mode = "compile-fail".parse().unwrap();
+    config.src_base = PathBuf::from("tests/compile-fail");
+    config.link_deps();
+    config.clean_rmeta();
+
+    compiletest::run_tests(&config);
+}
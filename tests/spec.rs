@@ -12,6 +12,7 @@ specific language governing permissions and limitations under the License.
 #![cfg(any(feature = "rsx-macro", feature = "css-macro"))]
 #![feature(proc_macro)]
 
+#[macro_use]
 extern crate rsx;
 extern crate rsx_primitives;
 
@@ -57,23 +58,23 @@ fn test_rsx_to_ast() {
     };
 
     let ast: RSXElement = rsx! {
-        <Dropdown show={props.visible}>
+        <dropdown show={props.visible}>
             A dropdown list
-            <Menu icon={props.menu.icon}>
-                <MenuItem>Do Something</MenuItem>
+            <menu icon={props.menu.icon}>
+                <menuitem>Do Something</menuitem>
                 {
                     if should_do_something_fun() {
-                        <MenuItem>Do{ what_fun() }</MenuItem>
+                        <menuitem>Do{ what_fun() }</menuitem>
                     } else {
-                        <MenuItem>Do{ what_else() }</MenuItem>
+                        <menuitem>Do{ what_else() }</menuitem>
                     }
                 }
-            </Menu>
-        </Dropdown>
+            </menu>
+        </dropdown>
     };
 
     let expected = RSXElement::Normal(RSXNormalElement(
-        RSXElementName::Name(RSXIdentifier("Dropdown".into())),
+        RSXElementName::Name(RSXIdentifier("dropdown".into())),
         RSXAttributes(vec![
             RSXAttribute::Named(
                 RSXAttributeName::Name(RSXIdentifier("show".into())),
@@ -85,7 +86,7 @@ fn test_rsx_to_ast() {
         RSXChildren(vec![
             RSXChild::Text(RSXText("A dropdown list".into())),
             RSXChild::Element(RSXElement::Normal(RSXNormalElement(
-                RSXElementName::Name(RSXIdentifier("Menu".into())),
+                RSXElementName::Name(RSXIdentifier("menu".into())),
                 RSXAttributes(vec![
                     RSXAttribute::Named(
                         RSXAttributeName::Name(RSXIdentifier("icon".into())),
@@ -96,13 +97,13 @@ fn test_rsx_to_ast() {
                 ]),
                 RSXChildren(vec![
                     RSXChild::Element(RSXElement::Normal(RSXNormalElement(
-                        RSXElementName::Name(RSXIdentifier("MenuItem".into())),
+                        RSXElementName::Name(RSXIdentifier("menuitem".into())),
                         RSXAttributes(vec![]),
                         RSXChildren(vec![RSXChild::Text(RSXText("Do Something".into()))])
                     ))),
                     RSXChild::CodeBlock(ArbitraryCodeRegion::RuntimeValue(ComputedValue(Box::new({
                         RSXElement::Normal(RSXNormalElement(
-                            RSXElementName::Name(RSXIdentifier("MenuItem".into())),
+                            RSXElementName::Name(RSXIdentifier("menuitem".into())),
                             RSXAttributes(vec![]),
                             RSXChildren(vec![
                                 RSXChild::Text(RSXText("Do".into())),
@@ -152,23 +153,23 @@ fn test_rsx_to_node() {
     };
 
     let node: Node = rsx! {
-        <Dropdown show={props.visible}>
+        <dropdown show={props.visible}>
             A dropdown list
-            <Menu icon={props.menu.icon}>
-                <MenuItem>Do Something</MenuItem>
+            <menu icon={props.menu.icon}>
+                <menuitem>Do Something</menuitem>
                 {
                     if should_do_something_fun() {
-                        <MenuItem>Do{ what_fun() }</MenuItem>
+                        <menuitem>Do{ what_fun() }</menuitem>
                     } else {
-                        <MenuItem>Do{ what_else() }</MenuItem>
+                        <menuitem>Do{ what_else() }</menuitem>
                     }
                 }
-            </Menu>
-        </Dropdown>
+            </menu>
+        </dropdown>
     };
 
     let expected = Node::Normal {
-        name: NodeName::Simple(Cow::from("Dropdown")),
+        name: NodeName::Simple(Cow::from("dropdown")),
         attributes: vec![
             Attribute(
                 AttributeName::Simple(Cow::from("show")),
@@ -180,7 +181,7 @@ fn test_rsx_to_node() {
                 contents: Cow::from("A dropdown list")
             },
             Node::Normal {
-                name: NodeName::Simple(Cow::from("Menu")),
+                name: NodeName::Simple(Cow::from("menu")),
                 attributes: vec![
                     Attribute(
                         AttributeName::Simple(Cow::from("icon")),
@@ -189,7 +190,7 @@ fn test_rsx_to_node() {
                 ],
                 children: vec![
                     Node::Normal {
-                        name: NodeName::Simple(Cow::from("MenuItem")),
+                        name: NodeName::Simple(Cow::from("menuitem")),
                         attributes: vec![],
                         children: vec![
                             Node::Text {
@@ -198,7 +199,7 @@ fn test_rsx_to_node() {
                         ]
                     },
                     Node::Normal {
-                        name: NodeName::Simple(Cow::from("MenuItem")),
+                        name: NodeName::Simple(Cow::from("menuitem")),
                         attributes: vec![],
                         children: vec![
                             Node::Text {
@@ -217,6 +218,507 @@ fn test_rsx_to_node() {
     assert_eq!(node, expected);
 }
 
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_text() {
+    let node: Node = rsx! {
+        <text>Don't panic, it's fine: 1 + 1 = 2! (Really.)</text>
+    };
+
+    let expected = Node::Normal {
+        name: NodeName::Simple(Cow::from("text")),
+        attributes: vec![],
+        children: vec![
+            Node::Text {
+                contents: Cow::from("Don't panic, it's fine: 1 + 1 = 2! (Really.)")
+            },
+        ]
+    };
+
+    assert_eq!(node, expected);
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_text_whitespace() {
+    fn text(contents: &'static str) -> Node {
+        Node::Text {
+            contents: Cow::from(contents)
+        }
+    }
+
+    fn element(name: &'static str, contents: &'static str) -> Node {
+        Node::Normal {
+            name: NodeName::Simple(Cow::from(name)),
+            attributes: vec![],
+            children: vec![text(contents)]
+        }
+    }
+
+    fn children(node: Node) -> Vec<Node> {
+        match node {
+            Node::Normal { children, .. } => children,
+            _ => panic!("Expected a normal node")
+        }
+    }
+
+    let cases: Vec<(Node, Vec<Node>)> = vec![
+        (rsx! { <div>Hello World</div> }, vec![text("Hello World")]),
+        (
+            rsx! {
+                <div>
+                    Hello World
+                </div>
+            },
+            vec![text("Hello World")]
+        ),
+        (
+            rsx! {
+                <div>
+                    Hello
+                    World
+                </div>
+            },
+            vec![text("Hello World")]
+        ),
+        (
+            rsx! {
+                <div>
+
+                    Hello World
+                </div>
+            },
+            vec![text("Hello World")]
+        ),
+        (
+            rsx! {
+                <div>
+                    Hello,
+                    <b>world</b>{" "}
+                    and goodbye.
+                </div>
+            },
+            vec![
+                text("Hello,"),
+                Node::Normal {
+                    name: NodeName::Simple(Cow::from("b")),
+                    attributes: vec![],
+                    children: vec![text("world")]
+                },
+                text(" "),
+                text("and goodbye."),
+            ]
+        ),
+        (
+            rsx! {
+                <div>
+                </div>
+            },
+            vec![]
+        ),
+        (
+            rsx! {
+                <div>
+                    { Some(<b>
+                        Hello
+                        World
+                    </b>) }
+                </div>
+            },
+            vec![element("b", "Hello World")]
+        ),
+        (
+            rsx!("tests/fixtures/test_2.rsx"),
+            vec![element("text", "Hello"), element("text", "  a b")]
+        ),
+    ];
+
+    for (node, expected) in cases {
+        assert_eq!(children(node), expected);
+    }
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_file() {
+    let greeting = "Hello world!";
+    let from_file: Node = rsx!("tests/fixtures/test_1.rsx");
+    let inline: Node = rsx! {
+        <view>
+            <text>{ greeting }</text>
+            <image src="..." />
+        </view>
+    };
+    let from_raw_path: Node = rsx!(r"tests/fixtures/test_1.rsx");
+
+    assert_eq!(from_file, inline);
+    assert_eq!(from_raw_path, inline);
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_file_desugaring() {
+    fn common() -> Vec<Attribute> {
+        vec![Attribute(AttributeName::Simple(Cow::from("id")), RuntimeValue::Str(Cow::from("common")))]
+    }
+
+    let greeting = "Hello world!";
+    let from_file: Node = rsx!("tests/fixtures/test_3.rsx");
+    let inline: Node = rsx! {
+        <view {...common()}>
+            // <text>Commented out</text>
+            <>
+                <text>{ greeting }</text>
+                /* <image src="commented out" /> */
+                <image src="..." />
+            </>
+        </view>
+    };
+
+    assert_eq!(from_file, inline);
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_fragments() {
+    fn text(name: &'static str, contents: &'static str) -> Node {
+        Node::Normal {
+            name: NodeName::Simple(Cow::from(name)),
+            attributes: vec![],
+            children: vec![
+                Node::Text {
+                    contents: Cow::from(contents)
+                },
+            ]
+        }
+    }
+
+    let node: Node = rsx! {
+        <view>
+            <>
+                <label>Name</label>
+                <input>Value</input>
+            </>
+            <text>Done</text>
+        </view>
+    };
+
+    assert_eq!(
+        node,
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("view")),
+            attributes: vec![],
+            children: vec![text("label", "Name"), text("input", "Value"), text("text", "Done")]
+        }
+    );
+
+    let fragment: Vec<Node> = rsx! {
+        <>
+            <label>Name</label>
+            <input>Value</input>
+        </>
+    };
+
+    assert_eq!(fragment, vec![text("label", "Name"), text("input", "Value")]);
+
+    let roots: Vec<Node> = rsx! {
+        <label>Name</label>
+        <input>Value</input>
+    };
+
+    assert_eq!(roots, fragment);
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_iterators() {
+    fn item(contents: &'static str) -> Node {
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("li")),
+            attributes: vec![],
+            children: vec![
+                Node::Text {
+                    contents: Cow::from(contents)
+                },
+            ]
+        }
+    }
+
+    fn list(children: Vec<Node>) -> Node {
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("ul")),
+            attributes: vec![],
+            children
+        }
+    }
+
+    let fruits = vec!["apple", "pear"];
+    let vegetables = vec!["leek"];
+    let nothing: Vec<&'static str> = vec![];
+
+    let node: Node = rsx! {
+        <ul>
+            <li>first</li>
+            { fruits.iter().map(|fruit| <li>{ *fruit }</li>) }
+            { vegetables.iter().chain(nothing.iter()).map(|vegetable| <li>{ *vegetable }</li>) }
+            <li>last</li>
+        </ul>
+    };
+
+    assert_eq!(
+        node,
+        list(vec![item("first"), item("apple"), item("pear"), item("leek"), item("last")])
+    );
+
+    let node: Node = rsx! {
+        <view>
+            <ul>{ fruits.iter().map(|fruit| <li>{ *fruit }</li>).collect::<Vec<_>>() }</ul>
+            <ul>{ nothing.iter().map(|fruit| <li>{ *fruit }</li>) }</ul>
+        </view>
+    };
+
+    assert_eq!(
+        node,
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("view")),
+            attributes: vec![],
+            children: vec![list(vec![item("apple"), item("pear")]), list(vec![])]
+        }
+    );
+
+    let rows = vec![fruits, vegetables];
+
+    let node: Node = rsx! {
+        <view>
+            { rows.iter().map(|row| <ul>{ row.iter().map(|cell| <li>{ *cell }</li>) }</ul>) }
+        </view>
+    };
+
+    assert_eq!(
+        node,
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("view")),
+            attributes: vec![],
+            children: vec![list(vec![item("apple"), item("pear")]), list(vec![item("leek")])]
+        }
+    );
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_keys() {
+    fn item(key: &'static str, contents: &'static str) -> Node {
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("li")),
+            attributes: vec![Attribute(AttributeName::Simple(Cow::from("key")), RuntimeValue::Str(Cow::from(key)))],
+            children: vec![
+                Node::Text {
+                    contents: Cow::from(contents)
+                },
+            ]
+        }
+    }
+
+    let second = "second";
+
+    let node: Node = rsx! {
+        <ul>
+            <li key="first">One</li>
+            <li key={second}>Two</li>
+        </ul>
+    };
+
+    assert_eq!(
+        node,
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("ul")),
+            attributes: vec![],
+            children: vec![item("first", "One"), item("second", "Two")]
+        }
+    );
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_optional_children() {
+    fn render(logged_in: bool) -> Node {
+        rsx! {
+            <view>
+                <text>Welcome</text>
+                { if logged_in { Some(<text>Logout</text>) } else { None } }
+                { () }
+            </view>
+        }
+    }
+
+    fn text(contents: &'static str) -> Node {
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("text")),
+            attributes: vec![],
+            children: vec![
+                Node::Text {
+                    contents: Cow::from(contents)
+                },
+            ]
+        }
+    }
+
+    assert_eq!(
+        render(true),
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("view")),
+            attributes: vec![],
+            children: vec![text("Welcome"), text("Logout")]
+        }
+    );
+    assert_eq!(
+        render(false),
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("view")),
+            attributes: vec![],
+            children: vec![text("Welcome")]
+        }
+    );
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_spread_attributes() {
+    let common = vec![
+        Attribute(AttributeName::Simple(Cow::from("disabled")), RuntimeValue::Boolean(true)),
+        Attribute(AttributeName::Simple(Cow::from("id")), RuntimeValue::Str(Cow::from("common"))),
+    ];
+
+    let node: Node = rsx! {
+        <view {...common.clone()} id="x">
+            <image src="..." {...common} />
+        </view>
+    };
+
+    assert_eq!(
+        node,
+        Node::Normal {
+            name: NodeName::Simple(Cow::from("view")),
+            attributes: vec![
+                Attribute(AttributeName::Simple(Cow::from("disabled")), RuntimeValue::Boolean(true)),
+                Attribute(AttributeName::Simple(Cow::from("id")), RuntimeValue::Str(Cow::from("x"))),
+            ],
+            children: vec![
+                Node::Normal {
+                    name: NodeName::Simple(Cow::from("image")),
+                    attributes: vec![
+                        Attribute(AttributeName::Simple(Cow::from("src")), RuntimeValue::Str(Cow::from("..."))),
+                        Attribute(AttributeName::Simple(Cow::from("disabled")), RuntimeValue::Boolean(true)),
+                        Attribute(AttributeName::Simple(Cow::from("id")), RuntimeValue::Str(Cow::from("common"))),
+                    ],
+                    children: vec![]
+                },
+            ]
+        }
+    );
+}
+
+#[cfg(feature = "rsx-macro")]
+#[test]
+fn test_rsx_components() {
+    #[derive(Props)]
+    struct Card {
+        title: String,
+        #[prop(default)]
+        bordered: bool
+    }
+
+    impl Card {
+        fn render(self, children: RSXChildren) -> RSXElement {
+            let mut contents = vec![RSXChild::Text(RSXText(self.title))];
+            if self.bordered {
+                contents.push(RSXChild::Text(RSXText("(bordered)".into())));
+            }
+            contents.extend(children.0);
+            RSXElement::Normal(RSXNormalElement(
+                RSXElementName::Name(RSXIdentifier("card".into())),
+                RSXAttributes(vec![]),
+                RSXChildren(contents)
+            ))
+        }
+    }
+
+    let node: Node = rsx! {
+        <view>
+            <Card title={"Greetings".to_string()} bordered>
+                <text>Hello world!</text>
+            </Card>
+            <Card title={"Farewell".to_string()} bordered={false} />
+            <Card title={"Goodbye".to_string()} />
+        </view>
+    };
+
+    let expected = Node::Normal {
+        name: NodeName::Simple(Cow::from("view")),
+        attributes: vec![],
+        children: vec![
+            Node::Normal {
+                name: NodeName::Simple(Cow::from("card")),
+                attributes: vec![],
+                children: vec![
+                    Node::Text {
+                        contents: Cow::from("Greetings")
+                    },
+                    Node::Text {
+                        contents: Cow::from("(bordered)")
+                    },
+                    Node::Normal {
+                        name: NodeName::Simple(Cow::from("text")),
+                        attributes: vec![],
+                        children: vec![
+                            Node::Text {
+                                contents: Cow::from("Hello world!")
+                            },
+                        ]
+                    },
+                ]
+            },
+            Node::Normal {
+                name: NodeName::Simple(Cow::from("card")),
+                attributes: vec![],
+                children: vec![
+                    Node::Text {
+                        contents: Cow::from("Farewell")
+                    },
+                ]
+            },
+            Node::Normal {
+                name: NodeName::Simple(Cow::from("card")),
+                attributes: vec![],
+                children: vec![
+                    Node::Text {
+                        contents: Cow::from("Goodbye")
+                    },
+                ]
+            },
+        ]
+    };
+
+    assert_eq!(node, expected);
+
+    let titles = vec!["Farewell", "Goodbye"];
+
+    let node: Node = rsx! {
+        <view>
+            { titles.iter().map(|title| <Card title={title.to_string()} />) }
+        </view>
+    };
+
+    let expected_nested: Node = rsx! {
+        <view>
+            <Card title={"Farewell".to_string()} />
+            <Card title={"Goodbye".to_string()} />
+        </view>
+    };
+
+    assert_eq!(node, expected_nested);
+}
+
 #[cfg(feature = "css-macro")]
 #[test]
 fn test_css_to_stylesheet_1() {
@@ -384,6 +886,235 @@ fn test_css_to_stylesheet_4() {
     assert_eq!(stylesheet, expected);
 }
 
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_file_paths() {
+    let from_manifest_dir = css!("tests/fixtures/test_1.css");
+    let from_source_file = css!("fixtures/test_1.css");
+    let from_raw_path = css!(r#"tests/fixtures/test_1.css"#);
+    let from_escaped_path = css!("tests\x2ffixtures/test_1.css");
+
+    assert_eq!(from_manifest_dir, from_source_file);
+    assert_eq!(from_manifest_dir, from_raw_path);
+    assert_eq!(from_manifest_dir, from_escaped_path);
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_imports() {
+    let from_file = css!("tests/fixtures/test_4.css");
+    let inline = css! {
+        @import "tests/fixtures/test_2.css";
+
+        .bar {
+            padding: 5px;
+        }
+    };
+
+    let imported = css!("tests/fixtures/test_2.css");
+    let own = css! {
+        .bar {
+            padding: 5px;
+        }
+    };
+    let expected = Stylesheet(imported.0.into_iter().chain(own.0).collect());
+
+    assert_eq!(from_file, expected);
+    assert_eq!(inline, expected);
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_variables() {
+    let stylesheet = css! {
+        :root {
+            --gap: 20px;
+            --half-gap: 10px;
+            --spacing: var(--half-gap);
+        }
+
+        .foo {
+            padding: var(--gap);
+            margin-left: var(--spacing);
+            margin-right: var(--missing, 5px);
+        }
+    };
+
+    let expected = css! {
+        .foo {
+            padding: 20px;
+            margin-left: 10px;
+            margin-right: 5px;
+        }
+    };
+
+    assert_eq!(stylesheet, expected);
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_themes() {
+    let themed = css! {
+        #[themed]
+        :root {
+            --gap: 20px;
+        }
+
+        .foo {
+            padding: var(--gap);
+            margin-left: var(--margin, 5px);
+        }
+    };
+
+    let light = css! {
+        .foo {
+            padding: 20px;
+            margin-left: 5px;
+        }
+    };
+
+    let dark = css! {
+        .foo {
+            padding: 10px;
+            margin-left: 1px;
+        }
+    };
+
+    assert_eq!(themed.theme(&[]).unwrap(), light);
+    assert_eq!(themed.theme(&[("--gap", "10px"), ("--margin", "1px")]).unwrap(), dark);
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_themes_indirect() {
+    let themed = css! {
+        #[themed]
+        :root {
+            --base: 10px;
+            --gap: var(--base);
+        }
+
+        .foo {
+            padding: var(--gap);
+            margin-left: var(--margin);
+        }
+    };
+
+    let default = css! {
+        .foo {
+            padding: 10px;
+            margin-left: initial;
+        }
+    };
+
+    let themed_base = css! {
+        .foo {
+            padding: 2px;
+            margin-left: 2px;
+        }
+    };
+
+    assert_eq!(themed.theme(&[]).unwrap(), default);
+    assert_eq!(themed.theme(&[("--base", "2px"), ("--margin", "var(--gap)")]).unwrap(), themed_base);
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_themes_errors() {
+    let themed = css! {
+        #[themed]
+        .foo {
+            padding: var(--gap);
+        }
+    };
+
+    assert!(themed.theme(&[("--gap", "var(--gap)")]).is_err());
+    assert!(themed.theme(&[("--gap", "10px; }")]).is_err());
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_to_stylesheet_5() {
+    let stylesheet = css! {
+        .a-b-c {
+            margin-left: 10px;
+        }
+
+        #id.class, #id .class {
+            padding-top: 1px;
+        }
+
+        .foo::before, .foo:hover {
+            width: 2px;
+        }
+
+        .a > .b, .a>.c, a > b {
+            flex-grow: 1.5;
+        }
+
+        .-webkit-foo, .bar-baz-qux {
+            flex-shrink: 2 !important;
+            align-self: flex-end;
+        }
+    };
+
+    let expected = css!("tests/fixtures/test_3.css");
+
+    assert_eq!(stylesheet, expected);
+    assert_eq!(stylesheet.0[0].selectors[0].selector, ".a-b-c");
+    assert_eq!(stylesheet.0[1].selectors[0].selector, "#id.class");
+    assert_eq!(stylesheet.0[1].selectors[1].selector, "#id .class");
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_to_stylesheet_units() {
+    // Lengths in `em` can only be written in files, since Rust's lexer
+    // reads `1.5em` as a number with a malformed exponent.
+    let mut from_file = css!("tests/fixtures/test_5.css");
+    let inline = css! {
+        .decimals {
+            flex-grow: 1.5;
+            width: 12.5px;
+            height: 50%;
+        }
+    };
+
+    assert_eq!(from_file.0[0], inline.0[0]);
+    assert_eq!(from_file.get(".em").len(), 2);
+}
+
+#[cfg(feature = "css-macro")]
+#[test]
+fn test_css_to_stylesheet_lenient() {
+    let stylesheet = css! {
+        #[lenient]
+        .foo {
+            bogus: 1px;
+            padding: 10px;
+        }
+    };
+
+    let expected = Stylesheet(vec![
+        StyleRule {
+            selectors: vec![
+                StyleSelector {
+                    selector: ".foo".into(),
+                    specificity: 1024u32
+                },
+            ],
+            declarations: vec![
+                StyleDeclaration::Layout(FlexStyle::PaddingTop(StyleUnit::Point(10f32.into()))),
+                StyleDeclaration::Layout(FlexStyle::PaddingRight(StyleUnit::Point(10f32.into()))),
+                StyleDeclaration::Layout(FlexStyle::PaddingBottom(StyleUnit::Point(10f32.into()))),
+                StyleDeclaration::Layout(FlexStyle::PaddingLeft(StyleUnit::Point(10f32.into()))),
+            ]
+        },
+    ]);
+
+    assert_eq!(stylesheet, expected);
+}
+
 #[cfg(all(feature = "rsx-macro", feature = "css-macro"))]
 #[test]
 fn test_rsx_and_css_1() {
@@ -421,7 +1152,7 @@ fn test_rsx_and_css_1() {
             ],
             children: vec![
                 Node::Text {
-                    contents: Cow::from("Hello world !")
+                    contents: Cow::from("Hello world!")
                 },
             ]
         }
@@ -451,13 +1182,99 @@ fn test_rsx_and_css_2() {
             ],
             children: vec![
                 Node::Text {
-                    contents: Cow::from("Hello world !")
+                    contents: Cow::from("Hello world!")
                 },
             ]
         }
     );
 }
 
+#[cfg(all(feature = "rsx-macro", feature = "css-macro"))]
+#[test]
+fn test_rsx_and_css_typed() {
+    let styles = css!(#[typed] "tests/fixtures/test_1.css");
+    let mut stylesheet = css!("tests/fixtures/test_1.css");
+
+    let node: Node = rsx! {
+        <view style={styles.root}>
+            <image style={styles.image} src="..." />
+        </view>
+    };
+
+    let expected: Node = rsx! {
+        <view style={stylesheet.get(".root")}>
+            <image style={stylesheet.get(".image")} src="..." />
+        </view>
+    };
+
+    assert_eq!(node, expected);
+
+    let styles = css! {
+        #[typed]
+        .bar-baz {
+            padding: 10px;
+        }
+    };
+
+    let mut stylesheet = css! {
+        .bar-baz {
+            padding: 10px;
+        }
+    };
+
+    let node: Node = rsx! { <view style={styles.bar_baz} /> };
+    let expected: Node = rsx! { <view style={stylesheet.get(".bar-baz")} /> };
+
+    assert_eq!(node, expected);
+}
+
+#[cfg(all(feature = "rsx-macro", feature = "css-macro"))]
+#[test]
+fn test_rsx_and_css_typed_named() {
+    css!(#[typed(Styles)] "tests/fixtures/test_1.css");
+
+    fn image(styles: Styles) -> Node {
+        rsx! { <image style={styles.image} src="..." /> }
+    }
+
+    let mut stylesheet = css!("tests/fixtures/test_1.css");
+    let expected: Node = rsx! { <image style={stylesheet.get(".image")} src="..." /> };
+
+    assert_eq!(image(Styles::new()), expected);
+    assert_eq!(Styles::new().class_name("image"), Some("image"));
+}
+
+#[cfg(all(feature = "rsx-macro", feature = "css-macro"))]
+#[test]
+fn test_rsx_and_css_scoped() {
+    let styles = css!(#[scoped] "tests/fixtures/test_1.css");
+    let other_styles = css! {
+        #[scoped]
+        .text {
+            height: 25px;
+        }
+    };
+    let mut stylesheet = css!("tests/fixtures/test_1.css");
+
+    let text = styles.class_name("text").unwrap();
+    assert_eq!(text, "text_e9c0785a");
+    assert_ne!(Some(text), other_styles.class_name("text"));
+    assert_eq!(styles.class_name("missing"), None);
+
+    let scoped_stylesheet = styles.stylesheet();
+    assert_eq!(scoped_stylesheet.0[0].selectors[0].selector, ".root_73ddeae1");
+    assert_eq!(scoped_stylesheet.0[1].selectors[0].selector, ".image_2f1b9c7e");
+    assert_eq!(scoped_stylesheet.0[2].selectors[0].selector, ".text_e9c0785a");
+
+    let (first, second) = (css!(#[scoped] .text { height: 1px; }), css!(#[scoped] .text { height: 1px; }));
+    assert_ne!(first.class_name("text"), second.class_name("text"));
+
+    let node: Node = rsx! { <text style={styles.text} /> };
+    let expected: Node = rsx! { <text style={stylesheet.get(".text")} /> };
+
+    assert_eq!(node, expected);
+}
+
 #[cfg(all(feature = "rsx-macro", feature = "css-macro"))]
 #[test]
 fn test_example_1() {
@@ -536,7 +1353,7 @@ fn test_example_1() {
                 ],
                 children: vec![
                     Node::Text {
-                        contents: "Hello world !".into()
+                        contents: "Hello world!".into()
                     },
                 ]
             },